@@ -0,0 +1,38 @@
+use goblin::archive::Archive;
+
+use crate::report::{BinaryReport, Member};
+use crate::{AnalyzeOptions, parse_report};
+
+/// Lists the members of an `ar` archive. With
+/// [`AnalyzeOptions::members`] each member is parsed and summarized as
+/// well; a member that fails to parse is recorded with its error rather than
+/// failing the whole archive.
+pub(crate) fn summarize(archive: &Archive, data: &[u8], options: &AnalyzeOptions) -> BinaryReport {
+    let mut report = BinaryReport::new("archive");
+    for index in 0..archive.len() {
+        let Some(member) = archive.get_at(index) else {
+            continue;
+        };
+        let start = member.offset as usize;
+        let bytes = data.get(start..start.saturating_add(member.size()));
+        let (member_report, error) = match bytes {
+            Some(bytes) if options.members => match parse_report(bytes, options) {
+                Ok(member_report) => (Some(member_report), None),
+                Err(err) => (None, Some(err.to_string())),
+            },
+            Some(_) => (None, None),
+            None => (
+                None,
+                Some(String::from("member extends past end of archive")),
+            ),
+        };
+        report.members.push(Member {
+            name: String::from(member.extended_name()),
+            offset: member.offset,
+            size: member.size() as u64,
+            report: member_report,
+            error,
+        });
+    }
+    report
+}
@@ -0,0 +1,119 @@
+//! An on-disk cache of reports, so re-scanning a corpus only parses the
+//! files that changed.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use sha2::{Digest, Sha256};
+
+use crate::hash::to_hex;
+use crate::report::BinaryReport;
+use crate::{AnalyzeError, AnalyzeOptions, Policy, SortKey};
+
+/// A directory of reports serialized as JSON. Each is named after the
+/// SHA-256 of the file it describes plus a fingerprint of the options and
+/// crate version that produced it, so changing either misses the cache
+/// rather than returning a report of the wrong shape.
+#[derive(Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    /// SHA-256s of the files looked up so far, which [`Cache::prune`]
+    /// keeps.
+    seen: Mutex<HashSet<String>>,
+}
+
+impl Cache {
+    /// Uses `dir` as the cache, creating it if needed.
+    pub fn open(dir: &Path) -> io::Result<Cache> {
+        fs::create_dir_all(dir)?;
+        Ok(Cache {
+            dir: dir.to_path_buf(),
+            seen: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Returns the cached report for `data` analyzed with `options`, or
+    /// the result of `analyze`, which is stored for next time. Reading or
+    /// writing the cache can fail without failing the analysis: an
+    /// unreadable or corrupt entry is a miss, and gets overwritten.
+    pub(crate) fn get_or_insert(
+        &self,
+        data: &[u8],
+        options: &AnalyzeOptions,
+        analyze: impl FnOnce() -> Result<BinaryReport, AnalyzeError>,
+    ) -> Result<BinaryReport, AnalyzeError> {
+        let sha256 = to_hex(&Sha256::digest(data));
+        // The policy, filter and sort order are applied to the cached
+        // report, so they don't change what gets stored.
+        let stored = AnalyzeOptions {
+            filter: None,
+            sort: SortKey::default(),
+            policy: Policy::default(),
+            ..options.clone()
+        };
+        let fingerprint = to_hex(&Sha256::digest(format!(
+            "{} {:?}",
+            env!("CARGO_PKG_VERSION"),
+            stored
+        )));
+        let entry = self
+            .dir
+            .join(format!("{}-{}.json", sha256, &fingerprint[..16]));
+        self.seen.lock().unwrap().insert(sha256);
+
+        if let Some(report) = fs::read_to_string(&entry)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+        {
+            return Ok(report);
+        }
+        let report = analyze()?;
+        let _ = self.store(&entry, &report);
+        Ok(report)
+    }
+
+    /// Writes to a temporary file and renames it, so a concurrent or
+    /// interrupted run never sees half an entry. Workers that analyze copies
+    /// of the same file write the same entry, so each gets its own temporary
+    /// file.
+    fn store(&self, entry: &Path, report: &BinaryReport) -> io::Result<()> {
+        static WRITES: AtomicU64 = AtomicU64::new(0);
+        let json = serde_json::to_string(report).map_err(io::Error::other)?;
+        let temporary = entry.with_extension(format!(
+            "{}-{}.tmp",
+            process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, entry)
+    }
+
+    /// Deletes the entries of files this cache hasn't been asked about,
+    /// returning how many were removed.
+    pub fn prune(&self) -> io::Result<usize> {
+        let seen = self.seen.lock().unwrap();
+        let mut removed = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some((sha256, _)) = name
+                .strip_suffix(".json")
+                .and_then(|key| key.split_once('-'))
+            else {
+                continue;
+            };
+            if !seen.contains(sha256) {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,12 @@
+/// Demangles Rust (legacy and v0) and Itanium C++ symbol names, returning
+/// `None` for names that aren't mangled.
+pub fn demangle(name: &str) -> Option<String> {
+    if let Ok(demangled) = rustc_demangle::try_demangle(name) {
+        return Some(format!("{:#}", demangled));
+    }
+    if name.starts_with("_Z") {
+        let symbol = cpp_demangle::Symbol::new(name).ok()?;
+        return symbol.demangle().ok();
+    }
+    None
+}
@@ -0,0 +1,174 @@
+//! Resolving the `DT_NEEDED` libraries of an ELF the way `ld.so` would,
+//! without running it.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use goblin::elf::Elf;
+
+use crate::report::Dependency;
+
+/// Searched after the configured directories, as glibc does.
+const DEFAULT_DIRS: [&str; 4] = ["/lib64", "/usr/lib64", "/lib", "/usr/lib"];
+
+/// What resolution needs from one object.
+struct Object {
+    needed: Vec<String>,
+    rpath: Vec<String>,
+    runpath: Vec<String>,
+    is_64: bool,
+    machine: u16,
+}
+
+impl Object {
+    /// Reads the dynamic section, expanding `$ORIGIN` to `origin`.
+    fn parse(elf: &Elf, origin: Option<&Path>) -> Object {
+        let expand = |paths: &[&str]| -> Vec<String> {
+            paths
+                .iter()
+                .flat_map(|path| path.split(':'))
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| match origin {
+                    Some(origin) => {
+                        let origin = origin.display().to_string();
+                        dir.replace("${ORIGIN}", &origin)
+                            .replace("$ORIGIN", &origin)
+                    }
+                    None => String::from(dir),
+                })
+                .collect()
+        };
+        Object {
+            needed: elf
+                .libraries
+                .iter()
+                .map(|name| String::from(*name))
+                .collect(),
+            rpath: expand(&elf.rpaths),
+            runpath: expand(&elf.runpaths),
+            is_64: elf.is_64,
+            machine: elf.header.e_machine,
+        }
+    }
+}
+
+/// Directories from `/etc/ld.so.conf`, following its `include` lines.
+fn configured_dirs(path: &Path, dirs: &mut Vec<String>, depth: usize) {
+    let Ok(text) = fs::read_to_string(path) else {
+        return;
+    };
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if let Some(pattern) = line.strip_prefix("include ") {
+            if depth > 8 {
+                continue;
+            }
+            let pattern = match Path::new(pattern.trim()) {
+                pattern if pattern.is_relative() => path.with_file_name(pattern),
+                pattern => pattern.to_path_buf(),
+            };
+            let included = glob::glob(&pattern.to_string_lossy());
+            for included in included.into_iter().flatten().flatten() {
+                configured_dirs(&included, dirs, depth + 1);
+            }
+        } else if !line.is_empty() && !line.starts_with("hwcap ") {
+            dirs.push(String::from(line));
+        }
+    }
+}
+
+struct Resolver {
+    library_path: Vec<String>,
+    system_dirs: Vec<String>,
+    /// Libraries whose dependencies have been listed already, by real path.
+    shown: HashSet<PathBuf>,
+}
+
+impl Resolver {
+    /// Finds `name` for `object`, returning its path and parsed contents.
+    /// `rpath` holds the `DT_RPATH`s of the object and its loaders.
+    fn find(&self, name: &str, object: &Object, rpath: &[String]) -> Option<(PathBuf, Object)> {
+        if name.contains('/') {
+            return self.load(Path::new(name), object);
+        }
+        rpath
+            .iter()
+            .chain(&self.library_path)
+            .chain(&object.runpath)
+            .chain(&self.system_dirs)
+            .find_map(|dir| self.load(&Path::new(dir).join(name), object))
+    }
+
+    /// Loads `path` if it is an ELF the loader could use for `object`, i.e.
+    /// one of the same class and machine.
+    fn load(&self, path: &Path, object: &Object) -> Option<(PathBuf, Object)> {
+        let loaded = crate::with_file(path, |data| {
+            let elf = Elf::parse(data).ok()?;
+            (elf.is_64 == object.is_64 && elf.header.e_machine == object.machine)
+                .then(|| Object::parse(&elf, path.parent()))
+        })
+        .ok()??;
+        Some((path.to_path_buf(), loaded))
+    }
+
+    fn children(&mut self, object: &Object, inherited: &[String]) -> Vec<Dependency> {
+        // `DT_RPATH` is ignored once the object has a `DT_RUNPATH`.
+        let rpath: Vec<String> = if object.runpath.is_empty() {
+            object.rpath.iter().chain(inherited).cloned().collect()
+        } else {
+            Vec::new()
+        };
+        object
+            .needed
+            .iter()
+            .map(|name| match self.find(name, object, &rpath) {
+                None => Dependency {
+                    name: name.clone(),
+                    path: None,
+                    already_shown: false,
+                    needed: Vec::new(),
+                },
+                Some((path, library)) => {
+                    // The same library is often reachable through symlinks.
+                    let real_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                    let already_shown = !self.shown.insert(real_path);
+                    Dependency {
+                        name: name.clone(),
+                        path: Some(path.display().to_string()),
+                        already_shown,
+                        needed: if already_shown {
+                            Vec::new()
+                        } else {
+                            self.children(&library, &rpath)
+                        },
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Resolves the libraries `elf` needs, and theirs in turn. A library is
+/// expanded only the first time it appears, which also breaks cycles.
+/// `path` is where the binary lives, if known, for `$ORIGIN`.
+pub(crate) fn dependency_tree(elf: &Elf, path: Option<&Path>) -> Vec<Dependency> {
+    let mut system_dirs = Vec::new();
+    configured_dirs(Path::new("/etc/ld.so.conf"), &mut system_dirs, 0);
+    system_dirs.extend(DEFAULT_DIRS.map(String::from));
+    let mut resolver = Resolver {
+        library_path: env::var("LD_LIBRARY_PATH")
+            .unwrap_or_default()
+            .split([':', ';'])
+            .filter(|dir| !dir.is_empty())
+            .map(String::from)
+            .collect(),
+        system_dirs,
+        shown: path
+            .and_then(|path| path.canonicalize().ok())
+            .into_iter()
+            .collect(),
+    };
+    resolver.children(&Object::parse(elf, path.and_then(Path::parent)), &[])
+}
@@ -0,0 +1,181 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::report::{BinaryReport, Checksec, ExportInfo, ImportInfo};
+
+/// One structural difference between two reports.
+#[derive(Debug, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum Change {
+    Added {
+        category: String,
+        item: String,
+    },
+    Removed {
+        category: String,
+        item: String,
+    },
+    Changed {
+        category: String,
+        old: String,
+        new: String,
+    },
+}
+
+/// Renders the change as one line of unified-style output, e.g.
+/// "+ import KERNEL32.dll!VirtualProtect".
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Added { category, item } => write!(f, "+ {} {}", category, item),
+            Change::Removed { category, item } => write!(f, "- {} {}", category, item),
+            Change::Changed { category, old, new } => {
+                write!(f, "~ {} {} -> {}", category, old, new)
+            }
+        }
+    }
+}
+
+fn diff_value(changes: &mut Vec<Change>, category: &str, old: Option<String>, new: Option<String>) {
+    if old != new {
+        let show = |value: Option<String>| value.unwrap_or_else(|| String::from("none"));
+        changes.push(Change::Changed {
+            category: String::from(category),
+            old: show(old),
+            new: show(new),
+        });
+    }
+}
+
+fn diff_sets(
+    changes: &mut Vec<Change>,
+    category: &str,
+    old: BTreeSet<String>,
+    new: BTreeSet<String>,
+) {
+    for item in old.difference(&new) {
+        changes.push(Change::Removed {
+            category: String::from(category),
+            item: item.clone(),
+        });
+    }
+    for item in new.difference(&old) {
+        changes.push(Change::Added {
+            category: String::from(category),
+            item: item.clone(),
+        });
+    }
+}
+
+fn import_name(import: &ImportInfo) -> String {
+    match &import.library {
+        Some(library) => format!("{}!{}", library, import.name),
+        None => import.name.clone(),
+    }
+}
+
+fn export_name(export: &ExportInfo) -> String {
+    match (&export.name, export.ordinal) {
+        (Some(name), _) => name.clone(),
+        (None, Some(ordinal)) => format!("Ordinal#{}", ordinal),
+        (None, None) => format!("<no-name>@{:#x}", export.rva),
+    }
+}
+
+fn checksec_items(checksec: &Option<Checksec>) -> [(&'static str, Option<String>); 4] {
+    let state = |enabled: bool| String::from(if enabled { "enabled" } else { "disabled" });
+    [
+        ("PIE", checksec.as_ref().map(|checksec| state(checksec.pie))),
+        ("NX", checksec.as_ref().map(|checksec| state(checksec.nx))),
+        (
+            "RELRO",
+            checksec.as_ref().map(|checksec| checksec.relro.clone()),
+        ),
+        (
+            "canary",
+            checksec.as_ref().map(|checksec| state(checksec.canary)),
+        ),
+    ]
+}
+
+/// Compares two reports, returning the changes grouped by category: header
+/// fields first, then libraries, hardening, sections, imports, exports and
+/// symbols. Items within a category are sorted by name.
+pub fn diff_reports(old: &BinaryReport, new: &BinaryReport) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    diff_value(
+        &mut changes,
+        "format",
+        Some(old.format.clone()),
+        Some(new.format.clone()),
+    );
+    diff_value(&mut changes, "machine", old.arch.clone(), new.arch.clone());
+    diff_value(
+        &mut changes,
+        "entry",
+        old.entry.map(|entry| format!("{:#x}", entry)),
+        new.entry.map(|entry| format!("{:#x}", entry)),
+    );
+    diff_value(
+        &mut changes,
+        "interpreter",
+        old.interpreter.clone(),
+        new.interpreter.clone(),
+    );
+
+    diff_sets(
+        &mut changes,
+        "library",
+        old.libraries.iter().cloned().collect(),
+        new.libraries.iter().cloned().collect(),
+    );
+
+    for ((name, old_state), (_, new_state)) in checksec_items(&old.checksec)
+        .into_iter()
+        .zip(checksec_items(&new.checksec))
+    {
+        diff_value(&mut changes, name, old_state, new_state);
+    }
+
+    diff_sets(
+        &mut changes,
+        "section",
+        old.sections
+            .iter()
+            .map(|section| section.name.clone())
+            .collect(),
+        new.sections
+            .iter()
+            .map(|section| section.name.clone())
+            .collect(),
+    );
+    diff_sets(
+        &mut changes,
+        "import",
+        old.imports.iter().map(import_name).collect(),
+        new.imports.iter().map(import_name).collect(),
+    );
+    diff_sets(
+        &mut changes,
+        "export",
+        old.exports.iter().map(export_name).collect(),
+        new.exports.iter().map(export_name).collect(),
+    );
+    diff_sets(
+        &mut changes,
+        "symbol",
+        old.symbols
+            .iter()
+            .map(|symbol| symbol.name.clone())
+            .collect(),
+        new.symbols
+            .iter()
+            .map(|symbol| symbol.name.clone())
+            .collect(),
+    );
+
+    changes
+}
@@ -0,0 +1,26 @@
+use crate::report::{DebugInfo, SectionInfo};
+
+/// The sections that make DWARF usable by a debugger; the others (ranges,
+/// string tables, ...) are only meaningful alongside them.
+const CORE_SECTIONS: [&str; 3] = ["info", "line", "abbrev"];
+
+/// Detects DWARF by section name, which works for ELF as well as PE files
+/// built by MinGW.
+pub(crate) fn from_sections(sections: &[SectionInfo]) -> DebugInfo {
+    let mut dwarf = false;
+    let mut size = 0;
+    for section in sections {
+        let Some(kind) = section
+            .name
+            .strip_prefix(".debug_")
+            .or_else(|| section.name.strip_prefix(".zdebug_"))
+        else {
+            continue;
+        };
+        dwarf |= CORE_SECTIONS.contains(&kind);
+        if section.file_backed {
+            size += section.size;
+        }
+    }
+    DebugInfo { dwarf, size }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+
+use goblin::elf::Elf;
+use goblin::elf::dynamic;
+use goblin::elf::header;
+use goblin::elf::note;
+use goblin::elf::program_header;
+use goblin::elf::reloc::{self, RelocSection};
+use goblin::elf::section_header;
+use goblin::elf::sym;
+
+use crate::demangle::demangle;
+use crate::hash::to_hex;
+use crate::report::{
+    BinaryReport, Checksec, ImportInfo, RelocationInfo, SectionInfo, SegmentInfo, SizeBreakdown,
+    SymbolInfo, TlsInfo,
+};
+use crate::{AnalyzeOptions, dwarf};
+
+fn machine_name(machine: u16) -> String {
+    let name = match machine {
+        header::EM_386 => "x86",
+        header::EM_X86_64 => "x86-64",
+        header::EM_ARM => "ARM",
+        header::EM_AARCH64 => "AArch64",
+        header::EM_MIPS => "MIPS",
+        header::EM_PPC => "PowerPC",
+        header::EM_PPC64 => "PowerPC64",
+        header::EM_S390 => "S/390",
+        header::EM_SPARCV9 => "SPARC V9",
+        header::EM_RISCV => "RISC-V",
+        header::EM_LOONGARCH => "LoongArch",
+        _ => return format!("unknown ({})", machine),
+    };
+    String::from(name)
+}
+
+/// Renders `sh_flags` with the letters `readelf -S` uses.
+fn section_flags(sh_flags: u64) -> String {
+    const LETTERS: [(u32, char); 12] = [
+        (section_header::SHF_WRITE, 'W'),
+        (section_header::SHF_ALLOC, 'A'),
+        (section_header::SHF_EXECINSTR, 'X'),
+        (section_header::SHF_MERGE, 'M'),
+        (section_header::SHF_STRINGS, 'S'),
+        (section_header::SHF_INFO_LINK, 'I'),
+        (section_header::SHF_LINK_ORDER, 'L'),
+        (section_header::SHF_OS_NONCONFORMING, 'O'),
+        (section_header::SHF_GROUP, 'G'),
+        (section_header::SHF_TLS, 'T'),
+        (section_header::SHF_COMPRESSED, 'C'),
+        (section_header::SHF_EXCLUDE, 'E'),
+    ];
+    LETTERS
+        .iter()
+        .filter(|(flag, _)| sh_flags & u64::from(*flag) != 0)
+        .map(|(_, letter)| *letter)
+        .collect()
+}
+
+fn sections(elf: &Elf) -> Vec<SectionInfo> {
+    elf.section_headers
+        .iter()
+        .map(|sh| SectionInfo {
+            name: String::from(elf.shdr_strtab.get_at(sh.sh_name).unwrap_or("<no-name>")),
+            kind: Some(String::from(section_header::sht_to_str(sh.sh_type))),
+            address: sh.sh_addr,
+            offset: sh.sh_offset,
+            size: sh.sh_size,
+            file_backed: sh.sh_type != section_header::SHT_NOBITS,
+            flags: section_flags(sh.sh_flags),
+            alignment: sh.sh_addralign,
+            entropy: None,
+        })
+        .collect()
+}
+
+fn segments(elf: &Elf) -> Vec<SegmentInfo> {
+    elf.program_headers
+        .iter()
+        .map(|phdr| SegmentInfo {
+            kind: String::from(program_header::pt_to_str(phdr.p_type)),
+            flags: format!(
+                "{}{}{}",
+                if phdr.is_read() { 'r' } else { '-' },
+                if phdr.is_write() { 'w' } else { '-' },
+                if phdr.is_executable() { 'x' } else { '-' }
+            ),
+            offset: phdr.p_offset,
+            address: phdr.p_vaddr,
+            file_size: phdr.p_filesz,
+            memory_size: phdr.p_memsz,
+        })
+        .collect()
+}
+
+/// Collects `.symtab` and `.dynsym` into one list in address order.
+fn symbols(elf: &Elf, options: &AnalyzeOptions) -> Vec<SymbolInfo> {
+    let tables = [
+        (&elf.syms, &elf.strtab, false),
+        (&elf.dynsyms, &elf.dynstrtab, true),
+    ];
+    let mut symbols: Vec<SymbolInfo> = Vec::new();
+    let mut seen: HashMap<(&str, u64), usize> = HashMap::new();
+    for (symtab, strtab, dynamic) in tables {
+        for symbol in symtab.iter() {
+            let name = strtab.get_at(symbol.st_name).unwrap_or("");
+            if name.is_empty() {
+                continue;
+            }
+            // `.symtab` spells imports with their version, as in
+            // "puts@GLIBC_2.2.5", where `.dynsym` has just "puts".
+            let key = (
+                name.split_once('@').map_or(name, |(base, _)| base),
+                symbol.st_value,
+            );
+            if let Some(&index) = seen.get(&key) {
+                symbols[index].dynamic |= dynamic;
+                continue;
+            }
+            seen.insert(key, symbols.len());
+            symbols.push(SymbolInfo {
+                name: String::from(name),
+                address: symbol.st_value,
+                size: symbol.st_size,
+                kind: String::from(sym::type_to_str(symbol.st_type())),
+                binding: String::from(sym::bind_to_str(symbol.st_bind())),
+                visibility: String::from(sym::visibility_to_str(symbol.st_visibility())),
+                section: match symbol.st_shndx as u32 {
+                    section_header::SHN_UNDEF => String::from("UND"),
+                    section_header::SHN_ABS => String::from("ABS"),
+                    section_header::SHN_COMMON => String::from("COM"),
+                    index => elf
+                        .section_headers
+                        .get(index as usize)
+                        .and_then(|sh| elf.shdr_strtab.get_at(sh.sh_name))
+                        .map_or_else(|| index.to_string(), String::from),
+                },
+                dynamic,
+                demangled: if options.demangle {
+                    demangle(name)
+                } else {
+                    None
+                },
+            });
+        }
+    }
+    symbols.sort_by_key(|symbol| symbol.address);
+    symbols
+}
+
+/// goblin's names are inconsistent about the `R_` prefix, and it has no
+/// name at all for many architectures.
+fn relocation_type_name(r_type: u32, machine: u16) -> String {
+    match reloc::r_to_str(r_type, machine) {
+        name if name.starts_with("R_UNKNOWN") => r_type.to_string(),
+        name if name.starts_with("R_") => String::from(name),
+        name => format!("R_{}", name),
+    }
+}
+
+fn relocations(elf: &Elf) -> Vec<RelocationInfo> {
+    let tables: [(&RelocSection, &str, &str); 3] = [
+        (&elf.dynrelas, ".rela.dyn", ".rela.dyn"),
+        (&elf.dynrels, ".rel.dyn", ".rel.dyn"),
+        (&elf.pltrelocs, ".rela.plt", ".rel.plt"),
+    ];
+    let mut relocations = Vec::new();
+    for (section, rela_name, rel_name) in tables {
+        for relocation in section.iter() {
+            let symbol = match relocation.r_sym {
+                0 => None,
+                index => elf
+                    .dynsyms
+                    .get(index)
+                    .and_then(|symbol| elf.dynstrtab.get_at(symbol.st_name))
+                    .filter(|name| !name.is_empty())
+                    .map(String::from),
+            };
+            relocations.push(RelocationInfo {
+                table: String::from(if relocation.r_addend.is_some() {
+                    rela_name
+                } else {
+                    rel_name
+                }),
+                offset: relocation.r_offset,
+                kind: relocation_type_name(relocation.r_type, elf.header.e_machine),
+                symbol,
+                addend: relocation.r_addend,
+            });
+        }
+    }
+    relocations
+}
+
+fn imports(elf: &Elf) -> Vec<ImportInfo> {
+    elf.dynsyms
+        .iter()
+        .filter(|symbol| symbol.is_import())
+        .filter_map(|symbol| elf.dynstrtab.get_at(symbol.st_name))
+        .filter(|name| !name.is_empty())
+        .map(|name| ImportInfo {
+            library: None,
+            name: String::from(name),
+            ordinal: None,
+        })
+        .collect()
+}
+
+fn checksec(elf: &Elf, symbols: &[SymbolInfo]) -> Checksec {
+    let has_segment = |p_type| elf.program_headers.iter().any(|phdr| phdr.p_type == p_type);
+
+    let pie = elf.header.e_type == header::ET_DYN && has_segment(program_header::PT_INTERP);
+
+    // Without a PT_GNU_STACK header the loader falls back to an executable stack.
+    let nx = elf
+        .program_headers
+        .iter()
+        .find(|phdr| phdr.p_type == program_header::PT_GNU_STACK)
+        .is_some_and(|phdr| !phdr.is_executable());
+
+    let bind_now = elf.dynamic.as_ref().is_some_and(|dynamic| {
+        dynamic
+            .dyns
+            .iter()
+            .any(|dyn_| dyn_.d_tag == dynamic::DT_BIND_NOW)
+            || dynamic.info.flags & dynamic::DF_BIND_NOW != 0
+            || dynamic.info.flags_1 & dynamic::DF_1_NOW != 0
+    });
+    let relro = match (has_segment(program_header::PT_GNU_RELRO), bind_now) {
+        (true, true) => "full",
+        (true, false) => "partial",
+        (false, _) => "disabled",
+    };
+
+    let canary = symbols.iter().any(|symbol| {
+        symbol.name == "__stack_chk_fail" || symbol.name.starts_with("__stack_chk_fail@")
+    });
+
+    Checksec {
+        pie,
+        nx,
+        relro: String::from(relro),
+        canary,
+    }
+}
+
+/// Decodes an `NT_GNU_ABI_TAG` descriptor: an OS code followed by the
+/// major, minor and patch of the oldest supported kernel.
+fn abi_tag(desc: &[u8], little_endian: bool) -> Option<String> {
+    let words: Vec<u32> = desc
+        .chunks_exact(4)
+        .take(4)
+        .map(|word| {
+            let word = [word[0], word[1], word[2], word[3]];
+            if little_endian {
+                u32::from_le_bytes(word)
+            } else {
+                u32::from_be_bytes(word)
+            }
+        })
+        .collect();
+    let [os, major, minor, patch] = words[..] else {
+        return None;
+    };
+    let os = match os {
+        0 => String::from("Linux"),
+        1 => String::from("GNU/Hurd"),
+        2 => String::from("Solaris"),
+        3 => String::from("FreeBSD"),
+        _ => format!("OS {}", os),
+    };
+    Some(format!("{} {}.{}.{}", os, major, minor, patch))
+}
+
+/// Returns the GNU build-id and ABI tag notes. Relocatable objects have no
+/// `PT_NOTE` segments, so their note sections are searched instead.
+fn gnu_notes(elf: &Elf, data: &[u8]) -> (Option<String>, Option<String>) {
+    let notes = elf
+        .iter_note_headers(data)
+        .or_else(|| elf.iter_note_sections(data, None));
+    let (mut build_id, mut abi) = (None, None);
+    for note in notes.into_iter().flatten().flatten() {
+        if note.name != "GNU" {
+            continue;
+        }
+        match note.n_type {
+            note::NT_GNU_BUILD_ID => build_id = Some(to_hex(note.desc)),
+            note::NT_GNU_ABI_TAG => abi = abi_tag(note.desc, elf.little_endian),
+            _ => {}
+        }
+    }
+    (build_id, abi)
+}
+
+fn sizes(elf: &Elf, data: &[u8], sections: &[SectionInfo]) -> SizeBreakdown {
+    let file_size = data.len() as u64;
+    let section_total: u64 = sections
+        .iter()
+        .filter(|section| section.file_backed)
+        .map(|section| section.size)
+        .sum();
+    let symbol_tables = elf
+        .section_headers
+        .iter()
+        .filter(|sh| {
+            matches!(
+                sh.sh_type,
+                section_header::SHT_SYMTAB
+                    | section_header::SHT_DYNSYM
+                    | section_header::SHT_STRTAB
+            )
+        })
+        .map(|sh| sh.sh_size)
+        .sum();
+    SizeBreakdown {
+        file_size,
+        sections: section_total,
+        symbol_tables,
+        unaccounted: file_size.saturating_sub(section_total),
+        overlay: None,
+    }
+}
+
+fn tls(elf: &Elf) -> Option<TlsInfo> {
+    let phdr = elf
+        .program_headers
+        .iter()
+        .find(|phdr| phdr.p_type == program_header::PT_TLS)?;
+    let tls_sections = |nobits: bool| {
+        elf.section_headers
+            .iter()
+            .filter(|sh| sh.sh_flags & u64::from(section_header::SHF_TLS) != 0)
+            .filter(|sh| (sh.sh_type == section_header::SHT_NOBITS) == nobits)
+            .map(|sh| sh.sh_size)
+            .sum()
+    };
+    Some(TlsInfo {
+        address: phdr.p_vaddr,
+        size: phdr.p_memsz,
+        alignment: phdr.p_align,
+        initialized: phdr.p_filesz,
+        zero_filled: phdr.p_memsz.saturating_sub(phdr.p_filesz),
+        tdata_sections: tls_sections(false),
+        tbss_sections: tls_sections(true),
+    })
+}
+
+pub(crate) fn summarize(elf: &Elf, data: &[u8], options: &AnalyzeOptions) -> BinaryReport {
+    let mut report = BinaryReport::new("ELF");
+    report.arch = Some(machine_name(elf.header.e_machine));
+    report.class = Some(String::from(if elf.is_64 { "ELF64" } else { "ELF32" }));
+    report.endianness = Some(String::from(if elf.little_endian {
+        "little"
+    } else {
+        "big"
+    }));
+    report.entry = Some(elf.entry);
+    report.load_base = elf
+        .program_headers
+        .iter()
+        .filter(|phdr| phdr.p_type == program_header::PT_LOAD)
+        .map(|phdr| phdr.p_vaddr)
+        .min();
+    // Executables and shared objects linked at 0 are relocated wherever the
+    // loader maps them; anything else runs at its link-time address.
+    report.aslr = report
+        .load_base
+        .map(|base| elf.header.e_type == header::ET_DYN && base == 0);
+    report.interpreter = elf.interpreter.map(String::from);
+    // `.dynsym` survives `strip`; only the full `.symtab` is removed.
+    report.stripped = Some(
+        !elf.section_headers
+            .iter()
+            .any(|sh| sh.sh_type == section_header::SHT_SYMTAB),
+    );
+    (report.build_id, report.abi_tag) = gnu_notes(elf, data);
+    report.libraries = elf.libraries.iter().map(|lib| String::from(*lib)).collect();
+    report.sections = sections(elf);
+    report.segments = segments(elf);
+    report.tls = tls(elf);
+    report.debug_info = Some(dwarf::from_sections(&report.sections));
+    report.sizes = Some(sizes(elf, data, &report.sections));
+    report.symbols = symbols(elf, options);
+    report.checksec = Some(checksec(elf, &report.symbols));
+    report.relocations = relocations(elf);
+    report.imports = imports(elf);
+    report
+}
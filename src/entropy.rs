@@ -0,0 +1,39 @@
+/// Sections above this entropy are most likely compressed or encrypted.
+pub const PACKED_ENTROPY: f64 = 7.0;
+
+/// Shannon entropy of `bytes` in bits per byte, from 0.0 to 8.0.
+pub fn shannon_entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        // Written as p * log2(1/p) so a section of one repeated byte gives
+        // 0.0 rather than -0.0, and folded from 0.0 because an empty `sum`
+        // of floats is -0.0 too.
+        .map(|&count| count as f64 / len * (len / count as f64).log2())
+        .fold(0.0, |total, term| total + term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_byte_is_positive_zero() {
+        for bytes in [&[0x90; 64][..], &[]] {
+            let entropy = shannon_entropy(bytes);
+            assert_eq!(entropy, 0.0);
+            assert!(entropy.is_sign_positive());
+        }
+    }
+
+    #[test]
+    fn uniform_bytes_are_eight_bits() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(shannon_entropy(&bytes), 8.0);
+    }
+}
@@ -0,0 +1,38 @@
+use std::io;
+
+use thiserror::Error;
+
+/// Why a binary could not be analyzed.
+#[derive(Debug, Error)]
+pub enum AnalyzeError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// goblin recognized the format but could not parse the contents.
+    #[error(transparent)]
+    Parse(#[from] goblin::error::Error),
+    /// goblin recognized the format but we have no summary for it, such as
+    /// bare COFF objects.
+    #[error("unsupported object format")]
+    UnsupportedFormat,
+    #[error("no section named '{name}' (available: {})", available.join(", "))]
+    SectionNotFound {
+        name: String,
+        available: Vec<String>,
+    },
+    #[error("section '{0}' extends past the end of the file")]
+    SectionOutOfBounds(String),
+}
+
+impl AnalyzeError {
+    /// A short machine-readable name for the variant, used as the `kind`
+    /// of `--json` error objects.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AnalyzeError::Io(_) => "io",
+            AnalyzeError::Parse(_) => "parse",
+            AnalyzeError::UnsupportedFormat => "unsupported",
+            AnalyzeError::SectionNotFound { .. } => "section_not_found",
+            AnalyzeError::SectionOutOfBounds(_) => "section_out_of_bounds",
+        }
+    }
+}
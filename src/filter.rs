@@ -0,0 +1,88 @@
+//! Narrowing and ordering the symbols of a report, applied by an
+//! [`Analyzer`](crate::Analyzer) after the analysis itself.
+
+use regex::Regex;
+
+use crate::report::BinaryReport;
+
+/// The order of [`BinaryReport::symbols`]. Sorting is stable, so symbols
+/// that compare equal keep their address order.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SortKey {
+    Name,
+    #[default]
+    Addr,
+    Size,
+}
+
+impl SortKey {
+    /// Parses a key as written on the command line: `name`, `addr` or
+    /// `size`.
+    pub fn parse(name: &str) -> Result<SortKey, String> {
+        match name {
+            "name" => Ok(SortKey::Name),
+            "addr" => Ok(SortKey::Addr),
+            "size" => Ok(SortKey::Size),
+            _ => Err(format!(
+                "unknown sort key '{}' (expected name, addr or size)",
+                name
+            )),
+        }
+    }
+
+    /// Sorts the symbols of `report` and of its archive members. They
+    /// already come in address order.
+    pub(crate) fn apply(self, report: &mut BinaryReport) {
+        match self {
+            SortKey::Name => report.symbols.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortKey::Addr => {}
+            SortKey::Size => report.symbols.sort_by_key(|symbol| symbol.size),
+        }
+        for member in &mut report.members {
+            if let Some(member_report) = &mut member.report {
+                self.apply(member_report);
+            }
+        }
+    }
+}
+
+/// A pattern matched against symbol, import and export names.
+#[derive(Clone, Debug)]
+pub enum NameFilter {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl NameFilter {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NameFilter::Substring(pattern) => name.contains(pattern.as_str()),
+            NameFilter::Regex(regex) => regex.is_match(name),
+        }
+    }
+
+    /// Drops the symbols, imports and exports that don't match, including
+    /// those of archive members. Symbols also match on their demangled name.
+    pub(crate) fn apply(&self, report: &mut BinaryReport) {
+        report.symbols.retain(|symbol| {
+            self.matches(&symbol.name)
+                || symbol
+                    .demangled
+                    .as_deref()
+                    .is_some_and(|name| self.matches(name))
+        });
+        report.imports.retain(|import| self.matches(&import.name));
+        report
+            .exports
+            .retain(|export| match (&export.name, export.ordinal) {
+                (Some(name), _) => self.matches(name),
+                (None, Some(ordinal)) => self.matches(&format!("Ordinal#{}", ordinal)),
+                (None, None) => false,
+            });
+        for member in &mut report.members {
+            if let Some(member_report) = &mut member.report {
+                self.apply(member_report);
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+use std::str::FromStr;
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::report::Hashes;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 3] = [
+        HashAlgorithm::Md5,
+        HashAlgorithm::Sha1,
+        HashAlgorithm::Sha256,
+    ];
+
+    /// Parses a comma-separated list such as `md5,sha256`.
+    pub fn parse_list(list: &str) -> Result<Vec<HashAlgorithm>, String> {
+        list.split(',').map(str::parse).collect()
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            _ => Err(format!("unknown hash algorithm '{}'", name)),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+        })
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub(crate) fn compute_hashes(data: &[u8], algorithms: &[HashAlgorithm]) -> Hashes {
+    let wants = |algorithm| algorithms.contains(&algorithm);
+    Hashes {
+        md5: wants(HashAlgorithm::Md5).then(|| to_hex(&Md5::digest(data))),
+        sha1: wants(HashAlgorithm::Sha1).then(|| to_hex(&Sha1::digest(data))),
+        sha256: wants(HashAlgorithm::Sha256).then(|| to_hex(&Sha256::digest(data))),
+    }
+}
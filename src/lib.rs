@@ -0,0 +1,274 @@
+//! Structured analysis of ELF, PE, Mach-O and `ar` binaries.
+//!
+//! [`analyze`] parses a binary held in memory and returns a
+//! [`BinaryReport`]. An [`Analyzer`] is configured once with
+//! [`AnalyzeOptions`] for the more expensive extras such as hashing or
+//! string extraction, then reused for every file; [`analyze_with`] and
+//! [`analyze_path`] are one-off shorthands for it.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use goblin::Object;
+use memmap2::Mmap;
+
+mod archive;
+mod cache;
+mod demangle;
+mod deps;
+mod diff;
+mod dwarf;
+mod elf;
+mod entropy;
+mod error;
+mod filter;
+mod hash;
+mod mach;
+mod pe;
+mod policy;
+mod report;
+mod strings;
+
+pub use cache::Cache;
+pub use demangle::demangle;
+pub use diff::{Change, diff_reports};
+pub use entropy::{PACKED_ENTROPY, shannon_entropy};
+pub use error::AnalyzeError;
+pub use filter::{NameFilter, SortKey};
+pub use hash::HashAlgorithm;
+pub use policy::Policy;
+pub use report::{
+    BinaryReport, Checksec, DebugInfo, Dependency, ExportInfo, FoundString, Hashes, ImportInfo,
+    LoadCommandInfo, Member, Overlay, RelocationInfo, RichEntry, RichHeader, SectionDump,
+    SectionInfo, SegmentInfo, SizeBreakdown, SymbolInfo, TlsInfo,
+};
+pub use strings::find_strings;
+
+/// Minimum string length used by [`AnalyzeOptions::strings`] when
+/// [`AnalyzeOptions::min_len`] is not set.
+pub const DEFAULT_MIN_LEN: usize = 4;
+
+/// Optional work performed by an [`Analyzer`]. The default does none of
+/// it, which is what [`analyze`] uses.
+#[derive(Clone, Debug, Default)]
+pub struct AnalyzeOptions {
+    /// Fill in [`SymbolInfo::demangled`] for Rust and C++ symbols.
+    pub demangle: bool,
+    /// Parse and summarize each member of an archive.
+    pub members: bool,
+    /// Compute [`BinaryReport::imphash`] for PE files.
+    pub imphash: bool,
+    /// Compute [`SectionInfo::entropy`] for every section.
+    pub entropy: bool,
+    /// Digests to put in [`BinaryReport::hashes`].
+    pub hashes: Vec<HashAlgorithm>,
+    /// `Some(None)` scans the whole file, `Some(Some(name))` a single section.
+    pub strings: Option<Option<String>>,
+    pub min_len: Option<usize>,
+    /// Also look for UTF-16LE strings.
+    pub utf16: bool,
+    /// Name of a section whose bytes go in [`BinaryReport::dump`].
+    pub dump: Option<String>,
+    /// Fill in [`BinaryReport::dependency_tree`] for ELF files.
+    pub deps_tree: bool,
+    /// Only keep the symbols, imports and exports whose name matches.
+    pub filter: Option<NameFilter>,
+    pub sort: SortKey,
+    /// Requirements to check, putting the failures in
+    /// [`BinaryReport::violations`].
+    pub policy: Policy,
+}
+
+/// Analyzes binaries with a fixed set of [`AnalyzeOptions`]. It keeps no
+/// per-file state beyond an optional [`Cache`], which is safe to share, so
+/// one `Analyzer` can be used by threads scanning files in parallel.
+#[derive(Clone, Debug, Default)]
+pub struct Analyzer {
+    options: AnalyzeOptions,
+    cache: Option<Arc<Cache>>,
+}
+
+impl Analyzer {
+    pub fn new(options: AnalyzeOptions) -> Self {
+        Analyzer {
+            options,
+            cache: None,
+        }
+    }
+
+    /// Makes [`Analyzer::analyze_file`] reuse reports from `cache` for files
+    /// it has seen before, and store the ones it hasn't. Dependency trees
+    /// depend on more than the file's contents, so they are resolved anew
+    /// every time.
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn options(&self) -> &AnalyzeOptions {
+        &self.options
+    }
+
+    /// Analyzes the file at `path`, or returns its cached report. The file
+    /// is memory-mapped so large binaries aren't read in full; files that
+    /// can't be mapped, such as pipes, are read into memory instead.
+    pub fn analyze_file(&self, path: &Path) -> Result<BinaryReport, AnalyzeError> {
+        with_file(path, |data| {
+            let mut report = match &self.cache {
+                Some(cache) => cache.get_or_insert(data, &self.options, || {
+                    analyze_contents(data, &self.options)
+                })?,
+                None => analyze_contents(data, &self.options)?,
+            };
+            add_dependency_tree(&mut report, data, Some(path), &self.options)?;
+            narrow(&mut report, &self.options);
+            Ok(report)
+        })?
+    }
+
+    /// Analyzes a binary held in memory. Without a path, `$ORIGIN` in the
+    /// library search paths of a `--deps-tree` can't be resolved.
+    pub fn analyze_bytes(&self, data: &[u8]) -> Result<BinaryReport, AnalyzeError> {
+        analyze_with(data, &self.options)
+    }
+}
+
+/// Calls `f` with the contents of the file at `path`, memory-mapped if
+/// possible.
+pub(crate) fn with_file<T>(path: &Path, f: impl FnOnce(&[u8]) -> T) -> io::Result<T> {
+    let mut file = File::open(path)?;
+    // SAFETY: the mapping is only read, and only for the duration of this
+    // call. If another process truncates the file meanwhile we may fault,
+    // which is the accepted trade-off of mmap-based tools.
+    match unsafe { Mmap::map(&file) } {
+        Ok(map) => Ok(f(&map)),
+        Err(_) => {
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            Ok(f(&data))
+        }
+    }
+}
+
+/// Analyzes `data` with the default options.
+pub fn analyze(data: &[u8]) -> Result<BinaryReport, AnalyzeError> {
+    Analyzer::default().analyze_bytes(data)
+}
+
+/// Analyzes the file at `path` with `options`; see [`Analyzer::analyze_file`].
+pub fn analyze_path(path: &Path, options: &AnalyzeOptions) -> Result<BinaryReport, AnalyzeError> {
+    Analyzer::new(options.clone()).analyze_file(path)
+}
+
+/// Analyzes `data`, doing the extra work requested in `options`.
+pub fn analyze_with(data: &[u8], options: &AnalyzeOptions) -> Result<BinaryReport, AnalyzeError> {
+    let mut report = analyze_contents(data, options)?;
+    add_dependency_tree(&mut report, data, None, options)?;
+    narrow(&mut report, options);
+    Ok(report)
+}
+
+/// Checks [`AnalyzeOptions::policy`], then applies [`AnalyzeOptions::filter`]
+/// and [`AnalyzeOptions::sort`]. This comes last so cached reports stay
+/// complete, and the policy sees every import whatever is filtered out.
+fn narrow(report: &mut BinaryReport, options: &AnalyzeOptions) {
+    report.violations = options.policy.violations(report);
+    if let Some(filter) = &options.filter {
+        filter.apply(report);
+    }
+    options.sort.apply(report);
+}
+
+/// Resolves the dependency tree of an ELF if `options` asks for it; `path`
+/// is where the binary lives, if known.
+fn add_dependency_tree(
+    report: &mut BinaryReport,
+    data: &[u8],
+    path: Option<&Path>,
+    options: &AnalyzeOptions,
+) -> Result<(), AnalyzeError> {
+    if options.deps_tree && report.format == "ELF" {
+        let elf = goblin::elf::Elf::parse(data)?;
+        report.dependency_tree = Some(deps::dependency_tree(&elf, path));
+    }
+    Ok(())
+}
+
+/// Everything that depends only on the contents of the file, and so can be
+/// cached.
+fn analyze_contents(data: &[u8], options: &AnalyzeOptions) -> Result<BinaryReport, AnalyzeError> {
+    let mut report = parse_report(data, options)?;
+    if options.entropy {
+        for section in &mut report.sections {
+            section.entropy = section.bytes(data).map(shannon_entropy);
+        }
+    }
+    if let Some(scope) = &options.strings {
+        let min_len = options.min_len.unwrap_or(DEFAULT_MIN_LEN);
+        let (bytes, base) = match scope {
+            None => (data, 0),
+            Some(name) => {
+                let section = find_section(&report.sections, name)?;
+                (section.bytes(data).unwrap_or(&[]), section.offset)
+            }
+        };
+        report.strings = Some(find_strings(bytes, base, min_len, options.utf16));
+    }
+    if let Some(name) = &options.dump {
+        let section = find_section(&report.sections, name)?;
+        let bytes = match section.bytes(data) {
+            Some(bytes) => Some(bytes.to_vec()),
+            None if !section.file_backed || section.size == 0 => None,
+            None => return Err(AnalyzeError::SectionOutOfBounds(name.clone())),
+        };
+        report.dump = Some(SectionDump {
+            section: section.name.clone(),
+            offset: section.offset,
+            bytes,
+        });
+    }
+    if !options.hashes.is_empty() {
+        report.hashes = Some(hash::compute_hashes(data, &options.hashes));
+    }
+    Ok(report)
+}
+
+/// Parses `data` into a report without any of the whole-file extras, which
+/// is also how archive members are summarized.
+pub(crate) fn parse_report(
+    data: &[u8],
+    options: &AnalyzeOptions,
+) -> Result<BinaryReport, AnalyzeError> {
+    let report = match Object::parse(data)? {
+        Object::Elf(elf) => elf::summarize(&elf, data, options),
+        Object::PE(pe) => pe::summarize(&pe, data, options),
+        Object::Mach(mach) => mach::summarize(&mach, data, options)?,
+        Object::Archive(ar) => archive::summarize(&ar, data, options),
+        Object::Unknown(magic) => {
+            let mut report = BinaryReport::new("unknown");
+            report.magic = Some(magic);
+            report
+        }
+        _ => return Err(AnalyzeError::UnsupportedFormat),
+    };
+    Ok(report)
+}
+
+fn find_section<'a>(
+    sections: &'a [SectionInfo],
+    name: &str,
+) -> Result<&'a SectionInfo, AnalyzeError> {
+    sections
+        .iter()
+        .find(|section| section.name == name)
+        .ok_or_else(|| AnalyzeError::SectionNotFound {
+            name: String::from(name),
+            available: sections
+                .iter()
+                .map(|section| section.name.clone())
+                .filter(|name| !name.is_empty())
+                .collect(),
+        })
+}
@@ -0,0 +1,202 @@
+use goblin::mach::cputype::get_arch_name_from_types;
+use goblin::mach::load_command::{self, CommandVariant, LoadCommand};
+use goblin::mach::{Mach, MachO, SingleArch};
+
+use crate::report::{BinaryReport, DebugInfo, LoadCommandInfo, Member};
+use crate::{AnalyzeOptions, archive};
+
+fn arch_name(cputype: u32, cpusubtype: u32) -> String {
+    match get_arch_name_from_types(cputype, cpusubtype) {
+        Some(name) => String::from(name),
+        None => format!("unknown (cputype {})", cputype),
+    }
+}
+
+/// Reads an `lc_str`, which is stored as an offset from the start of its
+/// load command.
+fn lc_str(data: &[u8], command: &LoadCommand, offset: u32) -> String {
+    let bytes = data
+        .get(command.offset + offset as usize..command.offset + command.command.cmdsize())
+        .unwrap_or(&[]);
+    let end = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// Mach-O packs versions as `xxxx.yy.zz` into 32 bits.
+fn packed_version(version: u32) -> String {
+    format!(
+        "{}.{}.{}",
+        version >> 16,
+        (version >> 8) & 0xff,
+        version & 0xff
+    )
+}
+
+fn segment_name(segname: &[u8; 16]) -> String {
+    let end = segname
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(segname.len());
+    String::from_utf8_lossy(&segname[..end]).into_owned()
+}
+
+fn format_uuid(uuid: &[u8; 16]) -> String {
+    let hex: String = uuid.iter().map(|byte| format!("{:02X}", byte)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn load_command_detail(command: &LoadCommand, data: &[u8]) -> Option<String> {
+    let detail = match &command.command {
+        CommandVariant::Segment32(segment) => format!(
+            "{} vmaddr {:#x} vmsize {:#x}",
+            segment_name(&segment.segname),
+            segment.vmaddr,
+            segment.vmsize
+        ),
+        CommandVariant::Segment64(segment) => format!(
+            "{} vmaddr {:#x} vmsize {:#x}",
+            segment_name(&segment.segname),
+            segment.vmaddr,
+            segment.vmsize
+        ),
+        CommandVariant::LoadDylib(dylib)
+        | CommandVariant::IdDylib(dylib)
+        | CommandVariant::LoadWeakDylib(dylib)
+        | CommandVariant::ReexportDylib(dylib)
+        | CommandVariant::LazyLoadDylib(dylib)
+        | CommandVariant::LoadUpwardDylib(dylib) => format!(
+            "{} (current {}, compatibility {})",
+            lc_str(data, command, dylib.dylib.name),
+            packed_version(dylib.dylib.current_version),
+            packed_version(dylib.dylib.compatibility_version)
+        ),
+        CommandVariant::LoadDylinker(dylinker)
+        | CommandVariant::IdDylinker(dylinker)
+        | CommandVariant::DyldEnvironment(dylinker) => lc_str(data, command, dylinker.name),
+        CommandVariant::Rpath(rpath) => lc_str(data, command, rpath.path),
+        CommandVariant::Main(main) => format!(
+            "entry offset {:#x} stack size {:#x}",
+            main.entryoff, main.stacksize
+        ),
+        CommandVariant::Uuid(uuid) => format_uuid(&uuid.uuid),
+        CommandVariant::CodeSignature(linkedit)
+        | CommandVariant::SegmentSplitInfo(linkedit)
+        | CommandVariant::FunctionStarts(linkedit)
+        | CommandVariant::DataInCode(linkedit)
+        | CommandVariant::DylibCodeSignDrs(linkedit)
+        | CommandVariant::LinkerOptimizationHint(linkedit)
+        | CommandVariant::DyldExportsTrie(linkedit)
+        | CommandVariant::DyldChainedFixups(linkedit) => format!(
+            "data offset {:#x} size {:#x}",
+            linkedit.dataoff, linkedit.datasize
+        ),
+        _ => return None,
+    };
+    Some(detail)
+}
+
+fn load_commands(macho: &MachO, data: &[u8]) -> Vec<LoadCommandInfo> {
+    macho
+        .load_commands
+        .iter()
+        .map(|command| {
+            let cmd = command.command.cmd();
+            let kind = match load_command::cmd_to_str(cmd) {
+                "LC_UNKNOWN" => format!("{:#x}", cmd),
+                name => String::from(name),
+            };
+            LoadCommandInfo {
+                kind,
+                offset: command.offset as u64,
+                size: command.command.cmdsize() as u64,
+                detail: load_command_detail(command, data),
+            }
+        })
+        .collect()
+}
+
+fn summarize_macho(macho: &MachO, data: &[u8]) -> BinaryReport {
+    let mut report = BinaryReport::new("Mach-O");
+    report.arch = Some(arch_name(macho.header.cputype(), macho.header.cpusubtype()));
+    report.class = Some(String::from(if macho.is_64 {
+        "Mach-O 64"
+    } else {
+        "Mach-O 32"
+    }));
+    report.endianness = Some(String::from(if macho.little_endian {
+        "little"
+    } else {
+        "big"
+    }));
+    report.entry = Some(macho.entry);
+    // goblin reports the binary itself as the first library.
+    report.libraries = macho
+        .libs
+        .iter()
+        .filter(|lib| **lib != "self")
+        .map(|lib| String::from(*lib))
+        .collect();
+    report.uuid = macho
+        .load_commands
+        .iter()
+        .find_map(|command| match &command.command {
+            CommandVariant::Uuid(uuid) => Some(format_uuid(&uuid.uuid)),
+            _ => None,
+        });
+    report.load_commands = load_commands(macho, data);
+    // Linked executables normally leave DWARF in the object files or a
+    // separate dSYM; only objects and dSYM companions have this segment.
+    let dwarf_size: Option<u64> = macho
+        .segments
+        .iter()
+        .filter(|segment| segment_name(&segment.segname) == "__DWARF")
+        .map(|segment| segment.filesize)
+        .reduce(|total, size| total + size);
+    report.debug_info = Some(DebugInfo {
+        dwarf: dwarf_size.is_some(),
+        size: dwarf_size.unwrap_or(0),
+    });
+    report
+}
+
+pub(crate) fn summarize(
+    mach: &Mach,
+    data: &[u8],
+    options: &AnalyzeOptions,
+) -> Result<BinaryReport, goblin::error::Error> {
+    let fat = match mach {
+        Mach::Binary(macho) => return Ok(summarize_macho(macho, data)),
+        Mach::Fat(fat) => fat,
+    };
+
+    let mut report = BinaryReport::new("Mach-O universal");
+    for (index, arch) in fat.iter_arches().enumerate() {
+        let arch = arch?;
+        let (slice, error) = match fat.get(index) {
+            Ok(SingleArch::MachO(macho)) => (Some(summarize_macho(&macho, arch.slice(data))), None),
+            Ok(SingleArch::Archive(ar)) => (
+                Some(archive::summarize(&ar, arch.slice(data), options)),
+                None,
+            ),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        report.members.push(Member {
+            name: arch_name(arch.cputype(), arch.cpusubtype()),
+            offset: u64::from(arch.offset),
+            size: u64::from(arch.size),
+            report: slice,
+            error,
+        });
+    }
+    Ok(report)
+}
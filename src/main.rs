@@ -1,31 +1,1617 @@
-use goblin::{Object, error};
+use arvora_nix::{
+    AnalyzeError, AnalyzeOptions, Analyzer, BinaryReport, Cache, Change, Checksec, Dependency,
+    ExportInfo, FoundString, HashAlgorithm, Hashes, ImportInfo, LoadCommandInfo, Member,
+    NameFilter, PACKED_ENTROPY, Policy, RelocationInfo, RichHeader, SectionDump, SectionInfo,
+    SegmentInfo, SizeBreakdown, SortKey, SymbolInfo, TlsInfo,
+};
+use owo_colors::{OwoColorize, Style};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
 use std::env;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::{self, ExitCode};
+use std::sync::Arc;
+use walkdir::WalkDir;
 
-fn goblin_runner(file_path: &PathBuf) -> Result<(), Box<dyn std::error::Error + 'static>> {
-    let file_data = fs::read(file_path)?;
-    println!("{:?}", file_data);
+/// Lists the sections matching `--section-regex` and `--exec-only`. With
+/// `-v` the table gains a flags column, and with `-vv` an alignment column
+/// as well.
+fn print_sections(
+    out: &mut impl Write,
+    sections: &[SectionInfo],
+    options: &Options,
+) -> io::Result<()> {
+    let mut header = format!(
+        "  [Nr] {:<24} {:<18} {:<18} {:<10} {:<10}",
+        "Name", "Type", "Address", "Offset", "Size"
+    );
+    if options.verbosity >= 1 {
+        header.push_str(&format!(" {:<5}", "Flags"));
+    }
+    if options.verbosity >= 2 {
+        header.push_str(" Align");
+    }
+    // Filtered listings keep each section's index in the full table.
+    let shown: Vec<(usize, &SectionInfo)> = sections
+        .iter()
+        .enumerate()
+        .filter(|(_, section)| !options.exec_only || section.flags.contains('X'))
+        .filter(|(_, section)| {
+            options
+                .section_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&section.name))
+        })
+        .collect();
+    if shown.is_empty() && (options.exec_only || options.section_regex.is_some()) {
+        writeln!(out, "  no matching sections")?;
+        return Ok(());
+    }
+    writeln!(out, "{}", header.trim_end())?;
+    for (index, section) in shown {
+        let mut line = format!(
+            "  [{:>2}] {:<24} {:<18} {:#018x} {:#010x} {:#010x}",
+            index,
+            section.name,
+            section.kind.as_deref().unwrap_or("-"),
+            section.address,
+            section.offset,
+            section.size
+        );
+        if options.verbosity >= 1 {
+            line.push_str(&format!(" {:<5}", section.flags));
+        }
+        if options.verbosity >= 2 {
+            line.push_str(&format!(" {}", section.alignment));
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// Applies `style` to `text` if color output is enabled. Escape codes throw
+/// off width formatting, so callers pad painted text themselves.
+fn paint(text: &str, style: Style, options: &Options) -> String {
+    if options.color {
+        text.style(style).to_string()
+    } else {
+        String::from(text)
+    }
+}
+
+fn print_segments(
+    out: &mut impl Write,
+    segments: &[SegmentInfo],
+    options: &Options,
+) -> io::Result<()> {
+    if segments.is_empty() {
+        writeln!(out, "  no program headers")?;
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "  {:<16} {:<5} {:<10} {:<18} {:<10} MemSize",
+        "Type", "Flags", "Offset", "VirtAddr", "FileSize"
+    )?;
+    for segment in segments {
+        let mut flags = segment.flags.clone();
+        if flags.contains(['w', 'x']) {
+            flags = paint(&flags, Style::new().red(), options);
+        }
+        flags.push_str(&" ".repeat(5usize.saturating_sub(segment.flags.len())));
+        writeln!(
+            out,
+            "  {:<16} {} {:#010x} {:#018x} {:#010x} {:#010x}",
+            segment.kind,
+            flags,
+            segment.offset,
+            segment.address,
+            segment.file_size,
+            segment.memory_size
+        )?;
+    }
+    Ok(())
+}
+
+fn print_load_commands(out: &mut impl Write, load_commands: &[LoadCommandInfo]) -> io::Result<()> {
+    if load_commands.is_empty() {
+        writeln!(out, "  no load commands")?;
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "  {:<24} {:<10} {:<8} Detail",
+        "Command", "Offset", "Size"
+    )?;
+    for command in load_commands {
+        let line = format!(
+            "  {:<24} {:#010x} {:<8} {}",
+            command.kind,
+            command.offset,
+            command.size,
+            command.detail.as_deref().unwrap_or("")
+        );
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+/// With `-v` the table gains binding and visibility columns, and with
+/// `-vv` the section each symbol is defined in.
+fn print_symbols(
+    out: &mut impl Write,
+    symbols: &[SymbolInfo],
+    options: &Options,
+) -> io::Result<()> {
+    if symbols.is_empty() {
+        writeln!(out, "  no symbols")?;
+        return Ok(());
+    }
+    let mut header = format!(
+        "  {:<18} {:<10} {:<8} {:<7}",
+        "Value", "Size", "Type", "Table"
+    );
+    if options.verbosity >= 1 {
+        header.push_str(&format!(" {:<7} {:<9}", "Bind", "Vis"));
+    }
+    if options.verbosity >= 2 {
+        header.push_str(&format!(" {:<18}", "Section"));
+    }
+    writeln!(out, "{} Name", header)?;
+    for symbol in symbols {
+        let mut line = format!(
+            "  {:#018x} {:#010x} {:<8} {:<7}",
+            symbol.address,
+            symbol.size,
+            symbol.kind,
+            if symbol.dynamic { "dynsym" } else { "symtab" }
+        );
+        if options.verbosity >= 1 {
+            line.push_str(&format!(" {:<7} {:<9}", symbol.binding, symbol.visibility));
+        }
+        if options.verbosity >= 2 {
+            line.push_str(&format!(" {:<18}", symbol.section));
+        }
+        writeln!(
+            out,
+            "{} {}",
+            line,
+            symbol.demangled.as_deref().unwrap_or(&symbol.name)
+        )?;
+    }
+    Ok(())
+}
+
+fn print_relocations(out: &mut impl Write, relocations: &[RelocationInfo]) -> io::Result<()> {
+    if relocations.is_empty() {
+        writeln!(out, "  no dynamic relocations")?;
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "  {:<10} {:<18} {:<24} Symbol + Addend",
+        "Table", "Offset", "Type"
+    )?;
+    for relocation in relocations {
+        let symbol = relocation.symbol.as_deref().unwrap_or("");
+        let target = match relocation.addend {
+            Some(addend) if symbol.is_empty() => format!("{:#x}", addend),
+            Some(addend) if addend < 0 => format!("{} - {:#x}", symbol, addend.unsigned_abs()),
+            Some(addend) if addend > 0 => format!("{} + {:#x}", symbol, addend),
+            _ => String::from(symbol),
+        };
+        writeln!(
+            out,
+            "  {:<10} {:#018x} {:<24} {}",
+            relocation.table, relocation.offset, relocation.kind, target
+        )?;
+    }
+    Ok(())
+}
+
+fn print_checksec(out: &mut impl Write, checksec: &Checksec) -> io::Result<()> {
+    let state = |enabled| if enabled { "enabled" } else { "disabled" };
+    writeln!(out, "  checksec:")?;
+    writeln!(out, "    PIE:    {}", state(checksec.pie))?;
+    writeln!(out, "    NX:     {}", state(checksec.nx))?;
+    writeln!(out, "    RELRO:  {}", checksec.relro)?;
+    writeln!(out, "    canary: {}", state(checksec.canary))?;
+    Ok(())
+}
+
+/// Prints imports grouped by library, with libraries in case-insensitive
+/// alphabetical order so listings diff cleanly between samples.
+fn print_imports(out: &mut impl Write, imports: &[ImportInfo]) -> io::Result<()> {
+    if imports.is_empty() {
+        writeln!(out, "  no imports")?;
+        return Ok(());
+    }
+    let mut libraries: Vec<Option<&str>> = Vec::new();
+    for import in imports {
+        if !libraries.contains(&import.library.as_deref()) {
+            libraries.push(import.library.as_deref());
+        }
+    }
+    libraries.sort_by_key(|library| library.map(str::to_lowercase));
+
+    writeln!(out, "  imports:")?;
+    for library in libraries {
+        let indent = match library {
+            Some(library) => {
+                writeln!(out, "    {}", library)?;
+                "      "
+            }
+            None => "    ",
+        };
+        for import in imports
+            .iter()
+            .filter(|import| import.library.as_deref() == library)
+        {
+            match import.ordinal {
+                Some(_) => writeln!(out, "{}{} (by ordinal)", indent, import.name)?,
+                None => writeln!(out, "{}{}", indent, import.name)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_exports(out: &mut impl Write, exports: &[ExportInfo]) -> io::Result<()> {
+    if exports.is_empty() {
+        writeln!(out, "  no exports")?;
+        return Ok(());
+    }
+    writeln!(out, "  {:<8} {:<10} Name", "Ordinal", "RVA")?;
+    for export in exports {
+        let ordinal = export
+            .ordinal
+            .map_or_else(|| String::from("-"), |ordinal| ordinal.to_string());
+        let name = match (&export.name, export.ordinal) {
+            (Some(name), _) => name.clone(),
+            (None, Some(ordinal)) => format!("Ordinal#{}", ordinal),
+            (None, None) => String::from("<no-name>"),
+        };
+        match &export.forwarder {
+            Some(forwarder) => writeln!(
+                out,
+                "  {:<8} {:#010x} {} -> {}",
+                ordinal, export.rva, name, forwarder
+            )?,
+            None => writeln!(out, "  {:<8} {:#010x} {}", ordinal, export.rva, name)?,
+        }
+    }
+    Ok(())
+}
+
+fn print_sizes(
+    out: &mut impl Write,
+    sizes: &SizeBreakdown,
+    format: &str,
+    options: &Options,
+) -> io::Result<()> {
+    writeln!(out, "  sizes:")?;
+    writeln!(out, "    {:<15} {:>12}", "file", sizes.file_size)?;
+    writeln!(out, "    {:<15} {:>12}", "sections", sizes.sections)?;
+    writeln!(
+        out,
+        "    {:<15} {:>12}",
+        "symbol tables", sizes.symbol_tables
+    )?;
+    writeln!(
+        out,
+        "    {:<15} {:>12}  (headers, padding, overlay)",
+        "unaccounted", sizes.unaccounted
+    )?;
+    match &sizes.overlay {
+        Some(overlay) => writeln!(
+            out,
+            "    {:<15} {:>12}  {}",
+            "overlay",
+            overlay.size,
+            paint(
+                &format!("appended after the last section, at {:#x}", overlay.offset),
+                Style::new().yellow(),
+                options
+            )
+        )?,
+        None if format == "PE" => writeln!(out, "    {:<15} {:>12}", "overlay", "none")?,
+        None => {}
+    }
+    Ok(())
+}
+
+/// Prints which function each `--addr` falls in, as `name+offset`, or
+/// failing that which section.
+fn print_addresses(
+    out: &mut impl Write,
+    report: &BinaryReport,
+    options: &Options,
+) -> io::Result<()> {
+    for &address in &options.addresses {
+        if let Some(symbol) = report.function_at(address) {
+            let name = arvora_nix::demangle(&symbol.name).unwrap_or_else(|| symbol.name.clone());
+            match address - symbol.address {
+                0 => writeln!(out, "  {:#x}: {}", address, name)?,
+                offset => writeln!(out, "  {:#x}: {}+{:#x}", address, name, offset)?,
+            }
+        } else if let Some(section) = report.section_at(address) {
+            writeln!(
+                out,
+                "  {:#x}: no symbol, in {}+{:#x}",
+                address,
+                section.name,
+                address - section.address
+            )?;
+        } else {
+            writeln!(out, "  {:#x}: not in any symbol or section", address)?;
+        }
+    }
+    Ok(())
+}
+
+/// Renders seconds since the Unix epoch as a UTC date and time.
+fn format_timestamp(timestamp: u32) -> String {
+    let days = i64::from(timestamp / 86400);
+    let seconds = timestamp % 86400;
+    // Howard Hinnant's days_from_civil, run backwards.
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
 
+fn print_rich_header(out: &mut impl Write, rich_header: &RichHeader) -> io::Result<()> {
+    writeln!(out, "  Rich header (key {:#010x}):", rich_header.key)?;
+    writeln!(
+        out,
+        "    {:<10} {:<7} {:<7} Count",
+        "comp.id", "Product", "Build"
+    )?;
+    for entry in &rich_header.entries {
+        writeln!(
+            out,
+            "    {:#010x} {:<7} {:<7} {}",
+            entry.comp_id, entry.product, entry.build, entry.count
+        )?;
+    }
+    Ok(())
+}
+
+fn print_dependency_tree(
+    out: &mut impl Write,
+    dependencies: &[Dependency],
+    depth: usize,
+    options: &Options,
+) -> io::Result<()> {
+    for dependency in dependencies {
+        let indent = "    ".repeat(depth);
+        match (&dependency.path, dependency.already_shown) {
+            (None, _) => writeln!(
+                out,
+                "    {}{} => {}",
+                indent,
+                dependency.name,
+                paint("MISSING", Style::new().red(), options)
+            )?,
+            (Some(path), true) => writeln!(
+                out,
+                "    {}{} => {} (already shown)",
+                indent, dependency.name, path
+            )?,
+            (Some(path), false) => {
+                writeln!(out, "    {}{} => {}", indent, dependency.name, path)?;
+                print_dependency_tree(out, &dependency.needed, depth + 1, options)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_tls(out: &mut impl Write, tls: &TlsInfo) -> io::Result<()> {
+    writeln!(out, "  TLS:")?;
+    writeln!(out, "    address:     {:#x}", tls.address)?;
+    writeln!(out, "    block size:  {:#x}", tls.size)?;
+    writeln!(out, "    alignment:   {:#x}", tls.alignment)?;
+    writeln!(
+        out,
+        "    initialized: {:#x} (.tdata sections: {:#x})",
+        tls.initialized, tls.tdata_sections
+    )?;
+    writeln!(
+        out,
+        "    zero-filled: {:#x} (.tbss sections: {:#x})",
+        tls.zero_filled, tls.tbss_sections
+    )?;
+    Ok(())
+}
+
+fn print_dependencies(out: &mut impl Write, libraries: &[String]) -> io::Result<()> {
+    if libraries.is_empty() {
+        writeln!(out, "  no dynamic dependencies")?;
+        return Ok(());
+    }
+    writeln!(out, "  needed:")?;
+    for library in libraries {
+        writeln!(out, "    {}", library)?;
+    }
+    Ok(())
+}
+
+fn print_entropy(
+    out: &mut impl Write,
+    sections: &[SectionInfo],
+    options: &Options,
+) -> io::Result<()> {
+    writeln!(out, "  entropy:")?;
+    for section in sections {
+        match section.entropy {
+            Some(entropy) if entropy > PACKED_ENTROPY => writeln!(
+                out,
+                "    {:<24} {:.3}  {}",
+                section.name,
+                entropy,
+                paint("likely packed/compressed", Style::new().yellow(), options)
+            )?,
+            Some(entropy) => writeln!(out, "    {:<24} {:.3}", section.name, entropy)?,
+            None => writeln!(out, "    {:<24} N/A", section.name)?,
+        }
+    }
+    Ok(())
+}
+
+fn print_strings(out: &mut impl Write, strings: &[FoundString]) -> io::Result<()> {
+    writeln!(out, "  strings:")?;
+    for string in strings {
+        let marker = if string.encoding == "ascii" {
+            ""
+        } else {
+            " (utf-16le)"
+        };
+        writeln!(
+            out,
+            "    {:#010x} {}{}",
+            string.offset, string.value, marker
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints `bytes` like `hexdump -C`, labelling each row with its file offset.
+fn print_hexdump(out: &mut impl Write, bytes: &[u8], base: u64) -> io::Result<()> {
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for index in 0..16 {
+            if index == 8 {
+                hex.push(' ');
+            }
+            match chunk.get(index) {
+                Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+                None => hex.push_str("   "),
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    char::from(byte)
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(out, "  {:08x}  {} |{}|", base + row as u64 * 16, hex, ascii)?;
+    }
+    Ok(())
+}
+
+fn print_dump(out: &mut impl Write, dump: &SectionDump) -> io::Result<()> {
+    match &dump.bytes {
+        Some(bytes) => {
+            writeln!(out, "  hexdump of {}:", dump.section)?;
+            print_hexdump(out, bytes, dump.offset)?;
+        }
+        None => writeln!(out, "  {} has no file data to dump", dump.section)?,
+    }
     Ok(())
 }
 
-fn main() {
+fn print_hashes(out: &mut impl Write, hashes: &Hashes) -> io::Result<()> {
+    let digests = [
+        ("md5", &hashes.md5),
+        ("sha1", &hashes.sha1),
+        ("sha256", &hashes.sha256),
+    ];
+    for (name, digest) in digests {
+        if let Some(digest) = digest {
+            writeln!(out, "  {:<11} {}", format!("{}:", name), digest)?;
+        }
+    }
+    Ok(())
+}
+
+fn print_summary(out: &mut impl Write, report: &BinaryReport, options: &Options) -> io::Result<()> {
+    let format = paint(&report.format, Style::new().bold(), options);
+    match report.magic {
+        Some(magic) => writeln!(out, "{} (magic: {:#x})", format, magic)?,
+        None => writeln!(out, "{}", format)?,
+    }
+    if let Some(arch) = &report.arch {
+        writeln!(out, "  machine:    {}", arch)?;
+    }
+    if let Some(class) = &report.class {
+        writeln!(out, "  class:      {}", class)?;
+    }
+    if let Some(endianness) = &report.endianness {
+        writeln!(out, "  endianness: {}", endianness)?;
+    }
+    if let Some(entry) = report.entry {
+        writeln!(out, "  entry:      {:#x}", entry)?;
+    }
+    if let (Some(load_base), Some(aslr)) = (report.load_base, report.aslr) {
+        let layout = match (report.format.as_str(), aslr) {
+            ("PE", true) => String::from("ASLR via DYNAMIC_BASE"),
+            ("PE", false) => paint("no ASLR, DYNAMIC_BASE not set", Style::new().red(), options),
+            (_, true) => String::from("position-independent, ASLR"),
+            (_, false) => paint("fixed address, no ASLR", Style::new().red(), options),
+        };
+        writeln!(out, "  load base:  {:#x} ({})", load_base, layout)?;
+    }
+    if report.format == "ELF" {
+        writeln!(
+            out,
+            "  interpreter: {}",
+            report
+                .interpreter
+                .as_deref()
+                .unwrap_or("statically linked / no interpreter")
+        )?;
+        writeln!(
+            out,
+            "  build-id:   {}",
+            report.build_id.as_deref().unwrap_or("no build-id")
+        )?;
+        if let Some(abi_tag) = &report.abi_tag {
+            writeln!(out, "  ABI tag:    {}", abi_tag)?;
+        }
+    }
+    if let Some(uuid) = &report.uuid {
+        writeln!(out, "  uuid:       {}", uuid)?;
+    }
+    if let Some(timestamp) = report.timestamp {
+        writeln!(
+            out,
+            "  timestamp:  {} ({:#010x})",
+            format_timestamp(timestamp),
+            timestamp
+        )?;
+    }
+    if let Some(linker_version) = &report.linker_version {
+        writeln!(out, "  linker:     {}", linker_version)?;
+    }
+    if report.format == "PE" {
+        match &report.rich_header {
+            Some(rich_header) if options.rich => print_rich_header(out, rich_header)?,
+            Some(rich_header) => writeln!(
+                out,
+                "  Rich header: {} entries (--rich to list)",
+                rich_header.entries.len()
+            )?,
+            None => writeln!(out, "  Rich header: none")?,
+        }
+    }
+    if let Some(stripped) = report.stripped {
+        writeln!(
+            out,
+            "  symbols:    {}",
+            if stripped { "stripped" } else { "not stripped" }
+        )?;
+    }
+    if let Some(debug_info) = &report.debug_info {
+        match (debug_info.dwarf, &report.uuid) {
+            (true, _) => writeln!(out, "  DWARF:      yes ({} bytes)", debug_info.size)?,
+            (false, Some(uuid)) => writeln!(
+                out,
+                "  DWARF:      none (look for a dSYM with UUID {})",
+                uuid
+            )?,
+            (false, None) => writeln!(out, "  DWARF:      none")?,
+        }
+    }
+    if let Some(hashes) = &report.hashes {
+        print_hashes(out, hashes)?;
+    }
+    if options.analyzer.options().imphash {
+        match report.imphash.as_deref() {
+            Some("") => writeln!(out, "  imphash:    (no imports)")?,
+            Some(imphash) => writeln!(out, "  imphash:    {}", imphash)?,
+            None => writeln!(out, "  imphash:    only available for PE")?,
+        }
+    }
+    if report.format == "ELF" || report.format == "PE" || report.format == "Mach-O" {
+        print_dependencies(out, &report.libraries)?;
+    }
+    if options.analyzer.options().deps_tree && report.format != "archive" {
+        match &report.dependency_tree {
+            Some(tree) if tree.is_empty() => {}
+            Some(tree) => {
+                writeln!(out, "  dependency tree:")?;
+                print_dependency_tree(out, tree, 0, options)?;
+            }
+            None => writeln!(out, "  dependency tree: only available for ELF")?,
+        }
+    }
+    if options.imports && report.format != "archive" {
+        print_imports(out, &report.imports)?;
+    }
+    if options.exports && report.format == "PE" {
+        print_exports(out, &report.exports)?;
+    }
+    if options.sections && !report.sections.is_empty() {
+        print_sections(out, &report.sections, options)?;
+    }
+    if options.segments && report.format == "ELF" {
+        print_segments(out, &report.segments, options)?;
+    }
+    if options.analyzer.options().entropy && !report.sections.is_empty() {
+        print_entropy(out, &report.sections, options)?;
+    }
+    if !options.addresses.is_empty() && report.format != "archive" {
+        print_addresses(out, report, options)?;
+    }
+    if options.tls {
+        match &report.tls {
+            Some(tls) => print_tls(out, tls)?,
+            None if report.format == "ELF" => writeln!(out, "  no TLS")?,
+            None => writeln!(out, "  TLS: only available for ELF")?,
+        }
+    }
+    if options.sizes {
+        match &report.sizes {
+            Some(sizes) => print_sizes(out, sizes, &report.format, options)?,
+            None => writeln!(out, "  sizes: only available for ELF and PE")?,
+        }
+    }
+    if options.checksec {
+        match &report.checksec {
+            Some(checksec) => print_checksec(out, checksec)?,
+            None => writeln!(out, "  checksec: only available for ELF")?,
+        }
+    }
+    if options.load_commands && report.format == "Mach-O" {
+        print_load_commands(out, &report.load_commands)?;
+    }
+    if options.symbols && report.format == "ELF" {
+        print_symbols(out, &report.symbols, options)?;
+    }
+    if options.relocs && report.format == "ELF" {
+        print_relocations(out, &report.relocations)?;
+    }
+    if let Some(strings) = &report.strings {
+        print_strings(out, strings)?;
+    }
+    if let Some(dump) = &report.dump {
+        print_dump(out, dump)?;
+    }
+    print_members(out, &report.members, options)?;
+    Ok(())
+}
+
+fn print_members(out: &mut impl Write, members: &[Member], options: &Options) -> io::Result<()> {
+    for member in members {
+        writeln!(
+            out,
+            "  {:<16} offset {:#010x}  size {:#010x}",
+            member.name, member.offset, member.size
+        )?;
+    }
+    for member in members {
+        match (&member.report, &member.error) {
+            (Some(report), _) => {
+                writeln!(out)?;
+                writeln!(out, "{}:", member.name)?;
+                print_summary(out, report, options)?;
+            }
+            (None, Some(error)) => {
+                writeln!(out)?;
+                writeln!(out, "{}:", member.name)?;
+                writeln!(out, "  error: {}", error)?;
+            }
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+/// A value of `--color`.
+enum ColorChoice {
+    Always,
+    Never,
+    Auto,
+}
+
+impl ColorChoice {
+    fn parse(name: &str) -> Result<ColorChoice, String> {
+        match name {
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            "auto" => Ok(ColorChoice::Auto),
+            _ => Err(format!(
+                "unknown color mode '{}' (expected always, never or auto)",
+                name
+            )),
+        }
+    }
+}
+
+/// A value of `--format`.
+#[derive(Clone, Copy, PartialEq)]
+enum FormatFilter {
+    Elf,
+    Pe,
+    Macho,
+    Archive,
+}
+
+impl FormatFilter {
+    fn parse(name: &str) -> Result<FormatFilter, String> {
+        match name {
+            "elf" => Ok(FormatFilter::Elf),
+            "pe" => Ok(FormatFilter::Pe),
+            "macho" => Ok(FormatFilter::Macho),
+            "archive" => Ok(FormatFilter::Archive),
+            _ => Err(format!(
+                "unknown format '{}' (expected elf, pe, macho or archive)",
+                name
+            )),
+        }
+    }
+
+    fn matches(self, report: &BinaryReport) -> bool {
+        match self {
+            FormatFilter::Elf => report.format == "ELF",
+            FormatFilter::Pe => report.format == "PE",
+            FormatFilter::Macho => report.format.starts_with("Mach-O"),
+            FormatFilter::Archive => report.format == "archive",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    sections: bool,
+    segments: bool,
+    load_commands: bool,
+    symbols: bool,
+    relocs: bool,
+    imports: bool,
+    exports: bool,
+    checksec: bool,
+    sizes: bool,
+    tls: bool,
+    rich: bool,
+    json: bool,
+    csv: bool,
+    /// -1 with `--quiet`, otherwise the number of `-v` flags.
+    verbosity: i8,
+    /// Resolved from `--color` and the environment; never set with `--json`
+    /// or `--csv`.
+    color: bool,
+    recursive: bool,
+    /// Size of the worker pool; `None` lets rayon use one thread per core.
+    jobs: Option<usize>,
+    /// Only report files of these formats; empty means all of them.
+    formats: Vec<FormatFilter>,
+    /// Only list sections whose name matches.
+    section_regex: Option<Regex>,
+    exec_only: bool,
+    /// `--addr` values to resolve to a symbol.
+    addresses: Vec<u64>,
+    /// Built once the arguments are parsed and shared by the workers.
+    analyzer: Analyzer,
+}
+
+/// Renders an I/O error without the trailing "(os error N)" that the
+/// standard library appends, e.g. "No such file or directory".
+fn io_error_message(err: &io::Error) -> String {
+    let message = err.to_string();
+    match message.find(" (os error") {
+        Some(index) => String::from(&message[..index]),
+        None => message,
+    }
+}
+
+/// A per-file failure. `kind` is [`AnalyzeError::kind`], or "io" when the
+/// path itself can't be accessed.
+struct FileError {
+    kind: &'static str,
+    message: String,
+}
+
+fn report_error(
+    out: &mut impl Write,
+    file: &str,
+    error: &FileError,
+    options: &Options,
+) -> io::Result<()> {
+    if options.json {
+        writeln!(
+            out,
+            "{}",
+            serde_json::json!({ "path": file, "error": error.message, "kind": error.kind })
+        )?;
+    } else {
+        eprintln!("error: {}", error.message);
+    }
+    Ok(())
+}
+
+/// Reports a failed write to stdout and returns true, unless the reader
+/// just went away, as `| head` does once it has its lines.
+fn write_failed(err: &io::Error) -> bool {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        return false;
+    }
+    eprintln!("error: cannot write output: {}", io_error_message(err));
+    true
+}
+
+#[derive(Serialize)]
+struct FileReport<'a> {
+    path: &'a str,
+    #[serde(flatten)]
+    report: &'a BinaryReport,
+}
+
+const CSV_HEADER: &str =
+    "path,format,arch,entry,num_sections,num_imports,num_symbols,sha256,pie,nx,stripped";
+
+/// Quotes `field` if it contains a comma, quote or line break, per RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        String::from(field)
+    }
+}
+
+/// One `--csv` row; fields that don't apply to the format are left empty.
+fn csv_row(name: &str, report: &BinaryReport) -> String {
+    let flag = |value: Option<bool>| value.map_or_else(String::new, |value| value.to_string());
+    let fields = [
+        String::from(name),
+        report.format.clone(),
+        report.arch.clone().unwrap_or_default(),
+        report
+            .entry
+            .map_or_else(String::new, |entry| format!("{:#x}", entry)),
+        report.sections.len().to_string(),
+        report.imports.len().to_string(),
+        report.symbols.len().to_string(),
+        report
+            .hashes
+            .as_ref()
+            .and_then(|hashes| hashes.sha256.clone())
+            .unwrap_or_default(),
+        flag(report.pie()),
+        flag(report.nx()),
+        flag(report.stripped),
+    ];
+    fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The path that means "read the binary from standard input".
+const STDIN_PATH: &str = "-";
+
+/// How `file` is named in the output; stdin has no path of its own.
+fn display_name(file: &str) -> &str {
+    if file == STDIN_PATH { "<stdin>" } else { file }
+}
+
+/// A file to analyze, either named on the command line or found by walking a
+/// directory with `--recursive`.
+struct Input {
+    path: String,
+    /// Found during a directory walk, so files that aren't binaries are
+    /// skipped quietly instead of being reported as "unknown".
+    discovered: bool,
+}
+
+/// Expands `arg` if it is a glob pattern, as Windows shells leave them for
+/// the program. Only arguments with glob metacharacters that don't name an
+/// existing file are expanded, so a file called `[1].exe` still works, and
+/// an invalid pattern is left to fail as a missing file.
+fn expand_glob(
+    out: &mut impl Write,
+    arg: &str,
+    options: &Options,
+    failed: &mut bool,
+) -> io::Result<Vec<String>> {
+    if !arg.contains(['*', '?', '[']) || Path::new(arg).exists() {
+        return Ok(vec![String::from(arg)]);
+    }
+    let Ok(entries) = glob::glob(arg) else {
+        return Ok(vec![String::from(arg)]);
+    };
+    let mut paths = Vec::new();
+    let mut unreadable = false;
+    for entry in entries {
+        match entry {
+            Ok(path) => paths.push(path.display().to_string()),
+            Err(err) => {
+                let file = err.path().display().to_string();
+                let error = FileError {
+                    kind: "io",
+                    message: format!(
+                        "cannot access '{}': {}",
+                        file,
+                        io_error_message(err.error())
+                    ),
+                };
+                report_error(out, &file, &error, options)?;
+                unreadable = true;
+            }
+        }
+    }
+    *failed |= unreadable;
+    if paths.is_empty() && !unreadable {
+        let error = FileError {
+            kind: "io",
+            message: format!("no files match '{}'", arg),
+        };
+        report_error(out, arg, &error, options)?;
+        *failed = true;
+    }
+    Ok(paths)
+}
+
+/// Expands the command-line paths into the list of files to analyze,
+/// expanding globs and reporting directories that can't be walked as it
+/// goes. Symlinks are not followed, so a link cycle can't make the walk run
+/// forever.
+fn collect_inputs(
+    out: &mut impl Write,
+    args: &[&String],
+    options: &Options,
+    failed: &mut bool,
+) -> io::Result<Vec<Input>> {
+    let mut inputs = Vec::new();
+    let mut paths = Vec::new();
+    for arg in args {
+        paths.extend(expand_glob(out, arg, options, failed)?);
+    }
+    for path in &paths {
+        if !options.recursive || !Path::new(path).is_dir() {
+            inputs.push(Input {
+                path: path.to_string(),
+                discovered: false,
+            });
+            continue;
+        }
+        for entry in WalkDir::new(path).sort_by_file_name() {
+            match entry {
+                Ok(entry) if entry.file_type().is_file() => inputs.push(Input {
+                    path: entry.path().display().to_string(),
+                    discovered: true,
+                }),
+                Ok(_) => {}
+                Err(err) => {
+                    let file = err
+                        .path()
+                        .map_or_else(|| path.to_string(), |path| path.display().to_string());
+                    let reason = err
+                        .io_error()
+                        .map_or_else(|| err.to_string(), io_error_message);
+                    let error = FileError {
+                        kind: "io",
+                        message: format!("cannot access '{}': {}", file, reason),
+                    };
+                    report_error(out, &file, &error, options)?;
+                    *failed = true;
+                }
+            }
+        }
+    }
+    Ok(inputs)
+}
+
+/// The number of bytes goblin needs to recognize a format.
+const MAGIC_LEN: u64 = 16;
+
+/// Analyzes a single input, returning `None` for a file that should be
+/// skipped: a discovered file that turned out not to be a binary, or one
+/// excluded by `--format`. Runs on a worker thread, so it must not
+/// print anything itself.
+fn analyze_input(input: &Input, options: &Options) -> Result<Option<BinaryReport>, FileError> {
+    let file = input.path.as_str();
+    let result = if file == STDIN_PATH {
+        let mut data = Vec::new();
+        io::stdin()
+            .lock()
+            .read_to_end(&mut data)
+            .map_err(AnalyzeError::from)
+            .and_then(|_| options.analyzer.analyze_bytes(&data))
+    } else {
+        let absolute_path = Path::new(file).canonicalize().map_err(|err| FileError {
+            kind: "io",
+            message: format!("cannot access '{}': {}", file, io_error_message(&err)),
+        })?;
+        // goblin rejects these as malformed rather than unknown.
+        if input.discovered
+            && fs::metadata(&absolute_path).is_ok_and(|metadata| metadata.len() < MAGIC_LEN)
+        {
+            return Ok(None);
+        }
+        options.analyzer.analyze_file(&absolute_path)
+    };
+
+    let report = result.map_err(|err| {
+        let message = match &err {
+            AnalyzeError::Io(io_err) => io_error_message(io_err),
+            _ => err.to_string(),
+        };
+        FileError {
+            kind: err.kind(),
+            message: format!("'{}': {}", display_name(file), message),
+        }
+    })?;
+    if input.discovered && report.format == "unknown" {
+        return Ok(None);
+    }
+    if !options.formats.is_empty() && !options.formats.iter().any(|format| format.matches(&report))
+    {
+        return Ok(None);
+    }
+    Ok(Some(report))
+}
+
+fn print_report(
+    out: &mut impl Write,
+    name: &str,
+    report: &BinaryReport,
+    show_name: bool,
+    options: &Options,
+) -> io::Result<()> {
+    if options.json {
+        let file_report = FileReport { path: name, report };
+        writeln!(out, "{}", serde_json::to_string(&file_report).unwrap())?;
+    } else if options.csv {
+        writeln!(out, "{}", csv_row(name, report))?;
+    } else {
+        if show_name {
+            writeln!(out, "{}:", name)?;
+        }
+        print_summary(out, report, options)?;
+    }
+    Ok(())
+}
+
+/// Exit status when at least one file could not be analyzed.
+const EXIT_FAILURE: u8 = 1;
+/// Exit status for bad command-line arguments.
+const EXIT_USAGE: u8 = 2;
+/// Exit status when every file was analyzed but one failed `--require-pie`
+/// or the `--policy`.
+const EXIT_POLICY: u8 = 3;
+
+/// Implements `arvora_nix diff [--json] <old> <new>`.
+fn run_diff(args: &[String]) -> ExitCode {
+    let mut options = Options::default();
+    let mut paths: Vec<&String> = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--json" => options.json = true,
+            _ if arg.starts_with('-') && arg != STDIN_PATH => {
+                usage_error(&format!("unknown option '{}'", arg));
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let [old_path, new_path] = paths[..] else {
+        usage_error("diff needs exactly two files");
+    };
+
+    let mut out = io::stdout().lock();
+    let mut reports = Vec::new();
+    for path in [old_path, new_path] {
+        let input = Input {
+            path: path.clone(),
+            discovered: false,
+        };
+        match analyze_input(&input, &options) {
+            Ok(Some(report)) => reports.push(report),
+            Ok(None) => unreachable!("diff inputs are never skipped"),
+            Err(error) => {
+                if let Err(err) = report_error(&mut out, display_name(path), &error, &options) {
+                    write_failed(&err);
+                }
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        }
+    }
+    let (old_name, new_name) = (display_name(old_path), display_name(new_path));
+    let changes = arvora_nix::diff_reports(&reports[0], &reports[1]);
+    match print_diff(&mut out, old_name, new_name, &changes, &options) {
+        Err(err) if write_failed(&err) => ExitCode::from(EXIT_FAILURE),
+        _ => ExitCode::SUCCESS,
+    }
+}
+
+fn print_diff(
+    out: &mut impl Write,
+    old_name: &str,
+    new_name: &str,
+    changes: &[Change],
+    options: &Options,
+) -> io::Result<()> {
+    if options.json {
+        let diff = serde_json::json!({ "old": old_name, "new": new_name, "changes": changes });
+        writeln!(out, "{}", diff)?;
+    } else {
+        writeln!(out, "--- {}", old_name)?;
+        writeln!(out, "+++ {}", new_name)?;
+        if changes.is_empty() {
+            writeln!(out, "no structural differences")?;
+        }
+        for change in changes {
+            writeln!(out, "{}", change)?;
+        }
+    }
+    Ok(())
+}
+
+const USAGE: &str = "\
+usage: arvora_nix [options] <file>...
+       arvora_nix diff [--json] <old> <new>
+
+A <file> of - reads the binary from standard input, and glob patterns such
+as *.exe are expanded even where the shell leaves them alone. The diff
+subcommand compares the entry point, libraries, hardening, sections,
+imports, exports and symbols of two binaries.
+
+options:
+  --sections    list section headers
+  --section-regex <pattern>
+                list only sections whose name matches the regular
+                expression; implies --sections
+  --exec-only   list only executable sections; implies --sections
+  --segments    list ELF program headers
+  --load-commands
+                list Mach-O load commands
+  --symbols     list ELF symbols (static and dynamic)
+  --sort <name|addr|size>
+                order the symbol listing (default: addr)
+  --addr <hex>  print the function containing an address, e.g. main+0x2c,
+                or the section if no symbol covers it; may be repeated
+  --relocs      list ELF dynamic and PLT relocations
+  --deps-tree   resolve the needed libraries of an ELF recursively, as the
+                loader would with LD_LIBRARY_PATH, DT_RPATH, DT_RUNPATH and
+                ld.so.conf, and print the tree
+  --imports     list imported symbols, grouped by library
+  --exports     list PE exports, including forwarders
+  --filter <pattern>
+                only list symbols, imports and exports whose name contains
+                pattern
+  --regex       treat the --filter pattern as a regular expression
+  --demangle    demangle Rust and C++ symbol names
+  --checksec    report ELF hardening (PIE, NX, RELRO, stack canary)
+  --sizes       break the file size down into sections, symbol tables and
+                the rest, and flag PE overlays
+  --tls         show the size, alignment and layout of ELF thread-local
+                storage
+  --rich        list the comp.id, build and count entries of a PE's Rich
+                header
+  --members     also summarize each member of an archive
+  --hash[=<algorithms>]
+                print md5, sha1 and sha256 of the file, or only the
+                comma-separated algorithms given
+  --imphash     print the import hash of a PE
+  --entropy     print the entropy of each section and flag packed ones
+  --strings[=<section>]
+                print printable strings in the file, or only in one section
+  --min-len <n> minimum string length for --strings (default 4)
+  --utf16       also find UTF-16LE strings, as used by Windows binaries
+  --dump <section>
+                hexdump the contents of a section
+  --json        print one JSON object per file
+  --csv         print one CSV row per file, after a header row
+  -q, --quiet   print nothing but errors; check the exit status instead
+  -v, --verbose show section flags and symbol binding and visibility;
+                repeat (-vv) to also show alignment and symbol sections
+  --color <always|never|auto>
+                color the output; auto (the default) colors only a terminal
+                and honors NO_COLOR
+  --format <elf|pe|macho|archive>
+                only report files of this format; may be repeated
+  -r, --recursive
+                analyze every file under directory arguments, skipping
+                files that aren't recognized binaries
+  --cache <dir> keep reports in dir, keyed by each file's sha256, and reuse
+                them for files that haven't changed
+  --cache-prune delete cached reports of files not analyzed in this run
+  --jobs <n>    analyze at most n files at once (default: one per CPU)
+  --require-pie fail with exit status 3 unless every binary is a PIE
+  --policy <file>
+                fail with exit status 3 unless every binary meets the
+                hardening policy in a TOML (or .json) file, with the keys
+                require_pie, require_nx, forbid_wx_segments and
+                forbid_imports
+
+exit status: 0 on success, 1 if any file could not be analyzed, 2 for bad
+arguments, 3 if a binary failed --require-pie or the --policy.
+";
+
+/// Analyzes every file named by `args` and prints the reports in argument
+/// order, noting in `failed` and `violated` whether any file couldn't be
+/// analyzed or broke the policy.
+fn analyze_all(
+    out: &mut impl Write,
+    args: &[&String],
+    options: &Options,
+    pool: &rayon::ThreadPool,
+    failed: &mut bool,
+    violated: &mut bool,
+) -> io::Result<()> {
+    let inputs = collect_inputs(out, args, options, failed)?;
+    let show_name = inputs.len() > 1 || options.recursive;
+
+    // Files are analyzed in parallel, but the results are collected before
+    // printing so the output follows the input order.
+    let results: Vec<_> = pool.install(|| {
+        inputs
+            .par_iter()
+            .map(|input| analyze_input(input, options))
+            .collect()
+    });
+
+    if options.csv && options.verbosity >= 0 {
+        writeln!(out, "{}", CSV_HEADER)?;
+    }
+    for (input, result) in inputs.iter().zip(results) {
+        let name = display_name(&input.path);
+        match result {
+            Ok(Some(report)) => {
+                if options.verbosity >= 0 {
+                    print_report(out, name, &report, show_name, options)?;
+                }
+                for violation in &report.violations {
+                    eprintln!("error: '{}': {}", name, violation);
+                    *violated = true;
+                }
+            }
+            Ok(None) => {}
+            Err(error) => {
+                report_error(out, name, &error, options)?;
+                *failed = true;
+            }
+        }
+    }
+    out.flush()
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    eprint!("{}", USAGE);
+    process::exit(i32::from(EXIT_USAGE));
+}
+
+fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
-    if !args.len() > 1 {
-        println!("No command-line arguments provided (except the executable path).");
+    if args.get(1).is_some_and(|arg| arg == "diff") {
+        return run_diff(&args[2..]);
+    }
+    let mut options = Options::default();
+    let mut user_args: Vec<&String> = Vec::new();
+    let mut pattern: Option<&String> = None;
+    let mut regex = false;
+    let mut color = ColorChoice::Auto;
+    let mut analysis = AnalyzeOptions::default();
+    let mut require_pie = false;
+    let mut cache_dir: Option<&String> = None;
+    let mut cache_prune = false;
+    let mut policy_path: Option<&String> = None;
+    let mut arg_iter = args.iter().skip(1);
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--sections" => options.sections = true,
+            "--segments" => options.segments = true,
+            "--load-commands" => options.load_commands = true,
+            "--symbols" => options.symbols = true,
+            "--relocs" => options.relocs = true,
+            "--imports" => options.imports = true,
+            "--exports" => options.exports = true,
+            "--demangle" => analysis.demangle = true,
+            "--checksec" => options.checksec = true,
+            "--sizes" => options.sizes = true,
+            "--tls" => options.tls = true,
+            "--rich" => options.rich = true,
+            "--members" => analysis.members = true,
+            "--deps-tree" => analysis.deps_tree = true,
+            "--hash" => analysis.hashes = HashAlgorithm::ALL.to_vec(),
+            "--imphash" => analysis.imphash = true,
+            "--entropy" => analysis.entropy = true,
+            "--strings" => analysis.strings = Some(None),
+            "--utf16" => analysis.utf16 = true,
+            "--json" => options.json = true,
+            "--csv" => options.csv = true,
+            "--recursive" | "-r" => options.recursive = true,
+            "--quiet" | "-q" => options.verbosity = -1,
+            "--verbose" => options.verbosity = options.verbosity.max(0).saturating_add(1),
+            _ if arg.len() > 1 && arg.starts_with('-') && arg[1..].bytes().all(|c| c == b'v') => {
+                let count = (arg.len() - 1).min(i8::MAX as usize) as i8;
+                options.verbosity = options.verbosity.max(0).saturating_add(count);
+            }
+            "--require-pie" => require_pie = true,
+            "--cache" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--cache needs a value"));
+                cache_dir = Some(value);
+            }
+            "--cache-prune" => cache_prune = true,
+            "--policy" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--policy needs a value"));
+                policy_path = Some(value);
+            }
+            "--dump" => {
+                let name = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--dump needs a section name"));
+                analysis.dump = Some(name.clone());
+            }
+            "--regex" => regex = true,
+            "--filter" => {
+                pattern = Some(
+                    arg_iter
+                        .next()
+                        .unwrap_or_else(|| usage_error("--filter needs a pattern")),
+                );
+            }
+            "--color" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--color needs a value"));
+                color = ColorChoice::parse(value).unwrap_or_else(|message| usage_error(&message));
+            }
+            "--section-regex" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--section-regex needs a value"));
+                options.section_regex = Some(Regex::new(value).unwrap_or_else(|err| {
+                    usage_error(&format!("invalid --section-regex: {}", err))
+                }));
+                options.sections = true;
+            }
+            "--exec-only" => {
+                options.exec_only = true;
+                options.sections = true;
+            }
+            "--sort" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--sort needs a value"));
+                analysis.sort =
+                    SortKey::parse(value).unwrap_or_else(|message| usage_error(&message));
+            }
+            "--format" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--format needs a value"));
+                for name in value.split(',') {
+                    let format =
+                        FormatFilter::parse(name).unwrap_or_else(|message| usage_error(&message));
+                    options.formats.push(format);
+                }
+            }
+            "--addr" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--addr needs a value"));
+                let digits = value
+                    .strip_prefix("0x")
+                    .or_else(|| value.strip_prefix("0X"))
+                    .unwrap_or(value);
+                options.addresses.push(
+                    u64::from_str_radix(digits, 16)
+                        .unwrap_or_else(|_| usage_error("--addr must be a hex address")),
+                );
+            }
+            "--min-len" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--min-len needs a value"));
+                analysis.min_len = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&len| len > 0)
+                        .unwrap_or_else(|| usage_error("--min-len must be a positive number")),
+                );
+            }
+            "--jobs" => {
+                let value = arg_iter
+                    .next()
+                    .unwrap_or_else(|| usage_error("--jobs needs a value"));
+                options.jobs = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&jobs| jobs > 0)
+                        .unwrap_or_else(|| usage_error("--jobs must be a positive number")),
+                );
+            }
+            _ if arg.starts_with("--strings=") => {
+                analysis.strings = Some(Some(String::from(&arg["--strings=".len()..])));
+            }
+            _ if arg.starts_with("--hash=") => {
+                analysis.hashes = HashAlgorithm::parse_list(&arg["--hash=".len()..])
+                    .unwrap_or_else(|message| usage_error(&message));
+            }
+            _ if arg.starts_with('-') && arg != STDIN_PATH => {
+                usage_error(&format!("unknown option '{}'", arg));
+            }
+            _ => user_args.push(arg),
+        }
     }
 
-    let mut file = String::from("");
-    let user_args: Vec<&String> = args.iter().skip(1).collect();
+    if options.json && options.csv {
+        usage_error("--json and --csv can't be combined");
+    }
+    if options.csv && !analysis.hashes.contains(&HashAlgorithm::Sha256) {
+        analysis.hashes.push(HashAlgorithm::Sha256);
+    }
+    analysis.filter = match (pattern, regex) {
+        (Some(pattern), false) => Some(NameFilter::Substring(pattern.clone())),
+        (Some(pattern), true) => Some(NameFilter::Regex(
+            Regex::new(pattern)
+                .unwrap_or_else(|err| usage_error(&format!("invalid --filter regex: {}", err))),
+        )),
+        (None, true) => usage_error("--regex needs --filter"),
+        (None, false) => None,
+    };
+    if let Some(path) = policy_path {
+        let text = fs::read_to_string(path).unwrap_or_else(|err| {
+            usage_error(&format!(
+                "cannot read policy '{}': {}",
+                path,
+                io_error_message(&err)
+            ))
+        });
+        let policy = if path.ends_with(".json") {
+            Policy::from_json(&text)
+        } else {
+            Policy::from_toml(&text)
+        };
+        analysis.policy = policy.unwrap_or_else(|message| {
+            usage_error(&format!("invalid policy '{}': {}", path, message))
+        });
+    }
+    analysis.policy.require_pie |= require_pie;
+    options.analyzer = Analyzer::new(analysis);
+    let cache = match cache_dir {
+        Some(dir) => match Cache::open(Path::new(dir)) {
+            Ok(cache) => Some(Arc::new(cache)),
+            Err(err) => {
+                eprintln!(
+                    "error: cannot use cache '{}': {}",
+                    dir,
+                    io_error_message(&err)
+                );
+                return ExitCode::from(EXIT_FAILURE);
+            }
+        },
+        None if cache_prune => usage_error("--cache-prune needs --cache"),
+        None => None,
+    };
+    if let Some(cache) = &cache {
+        options.analyzer = options.analyzer.with_cache(Arc::clone(cache));
+    }
 
-    file = String::from(user_args[0]);
+    options.color = !options.json
+        && !options.csv
+        && match color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            // https://no-color.org: any non-empty value disables color.
+            ColorChoice::Auto => {
+                io::stdout().is_terminal()
+                    && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+        };
 
-    let exe_path = Path::new(&file);
-    println!("{:?}", exe_path);
+    if user_args.is_empty() {
+        eprint!("{}", USAGE);
+        return ExitCode::from(EXIT_USAGE);
+    }
 
-    let absolute_path = exe_path.canonicalize().unwrap();
-    println!("Absolute path: {:?}", &absolute_path);
+    let pool = match rayon::ThreadPoolBuilder::new()
+        .num_threads(options.jobs.unwrap_or(0))
+        .build()
+    {
+        Ok(pool) => pool,
+        Err(err) => {
+            eprintln!("error: cannot start worker threads: {}", err);
+            return ExitCode::from(EXIT_FAILURE);
+        }
+    };
+    let mut failed = false;
+    let mut violated = false;
+    let mut out = io::stdout().lock();
+    match analyze_all(
+        &mut out,
+        &user_args,
+        &options,
+        &pool,
+        &mut failed,
+        &mut violated,
+    ) {
+        // A partial run hasn't seen every file, so it mustn't prune.
+        Err(err) => failed |= write_failed(&err),
+        Ok(()) => {
+            if let Some(cache) = cache.filter(|_| cache_prune) {
+                match cache.prune() {
+                    Ok(removed) if options.verbosity >= 1 => {
+                        eprintln!("pruned {} stale cache entries", removed)
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("error: cannot prune cache: {}", io_error_message(&err));
+                        failed = true;
+                    }
+                }
+            }
+        }
+    }
 
-    goblin_runner(&absolute_path);
+    if failed {
+        ExitCode::from(EXIT_FAILURE)
+    } else if violated {
+        ExitCode::from(EXIT_POLICY)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_timestamps_as_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951782400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(1700000000), "2023-11-14 22:13:20 UTC");
+        assert_eq!(format_timestamp(u32::MAX), "2106-02-07 06:28:15 UTC");
+    }
+
+    #[test]
+    fn quotes_csv_fields_per_rfc_4180() {
+        assert_eq!(csv_field("hello"), "hello");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
 }
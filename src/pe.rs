@@ -0,0 +1,330 @@
+use goblin::pe::PE;
+use goblin::pe::dll_characteristic;
+use goblin::pe::export::{ExportAddressTableEntry, Reexport};
+use goblin::pe::import::Import;
+use goblin::pe::section_table;
+use md5::Md5;
+use sha2::Digest;
+
+use crate::hash::to_hex;
+use crate::report::{
+    BinaryReport, ExportInfo, ImportInfo, Overlay, RichEntry, RichHeader, SectionInfo,
+    SizeBreakdown,
+};
+use crate::{AnalyzeOptions, dwarf};
+
+fn dependencies<'a>(pe: &PE<'a>) -> Vec<&'a str> {
+    let mut dlls: Vec<&str> = Vec::new();
+    for import in &pe.imports {
+        if !dlls.contains(&import.dll) {
+            dlls.push(import.dll);
+        }
+    }
+    dlls
+}
+
+/// Renders section characteristics as "RWX" permissions followed by what
+/// the section holds: Code, Initialized or Uninitialized data, and whether
+/// it is Discardable or Shared.
+fn section_flags(characteristics: u32) -> String {
+    const LETTERS: [(u32, char); 8] = [
+        (section_table::IMAGE_SCN_MEM_READ, 'R'),
+        (section_table::IMAGE_SCN_MEM_WRITE, 'W'),
+        (section_table::IMAGE_SCN_MEM_EXECUTE, 'X'),
+        (section_table::IMAGE_SCN_CNT_CODE, 'C'),
+        (section_table::IMAGE_SCN_CNT_INITIALIZED_DATA, 'I'),
+        (section_table::IMAGE_SCN_CNT_UNINITIALIZED_DATA, 'U'),
+        (section_table::IMAGE_SCN_MEM_DISCARDABLE, 'D'),
+        (section_table::IMAGE_SCN_MEM_SHARED, 'S'),
+    ];
+    LETTERS
+        .iter()
+        .filter(|(flag, _)| characteristics & flag != 0)
+        .map(|(_, letter)| *letter)
+        .collect()
+}
+
+/// `IMAGE_SCN_ALIGN_*` stores log2 of the alignment plus one; it is only
+/// meaningful in object files, so images usually report 0.
+fn section_alignment(characteristics: u32) -> u64 {
+    match (characteristics & section_table::IMAGE_SCN_ALIGN_MASK) >> 20 {
+        0 => 0,
+        exponent => 1 << (exponent - 1),
+    }
+}
+
+fn sections(pe: &PE) -> Vec<SectionInfo> {
+    pe.sections
+        .iter()
+        .map(|section| SectionInfo {
+            name: String::from(section.name().unwrap_or("<no-name>")),
+            kind: None,
+            address: u64::from(section.virtual_address),
+            offset: u64::from(section.pointer_to_raw_data),
+            size: u64::from(section.size_of_raw_data),
+            file_backed: section.size_of_raw_data != 0,
+            flags: section_flags(section.characteristics),
+            alignment: section_alignment(section.characteristics),
+            entropy: None,
+        })
+        .collect()
+}
+
+/// goblin gives ordinal-only imports a synthetic "ORDINAL <n>" name and no
+/// hint/name table RVA.
+fn import_is_ordinal(import: &Import) -> bool {
+    import.rva == 0 && import.name.starts_with("ORDINAL ")
+}
+
+/// Computes the import hash popularized by Mandiant and `pefile`: every
+/// imported `dll.function` pair, lowercased and with the `.dll`/`.ocx`/`.sys`
+/// extension dropped, joined with commas and MD5'd. Ordinal imports are
+/// rendered as `ord<N>`; unlike `pefile` we don't carry its name tables for
+/// the handful of DLLs commonly imported by ordinal. A PE without imports
+/// yields an empty string.
+fn imphash(pe: &PE) -> String {
+    let imports: Vec<_> = pe
+        .imports
+        .iter()
+        .map(|import| {
+            let name = (!import_is_ordinal(import)).then_some(&*import.name);
+            (import.dll, name, import.ordinal)
+        })
+        .collect();
+    hash_imports(&imports)
+}
+
+/// [`imphash`] of `(dll, name, ordinal)` triples, where `name` is `None` for
+/// imports by ordinal.
+fn hash_imports(imports: &[(&str, Option<&str>, u16)]) -> String {
+    if imports.is_empty() {
+        return String::new();
+    }
+    let entries: Vec<String> = imports
+        .iter()
+        .map(|&(dll, name, ordinal)| {
+            let dll = dll.to_lowercase();
+            let library = match dll.rsplit_once('.') {
+                Some((stem, "dll" | "ocx" | "sys")) => stem,
+                _ => dll.as_str(),
+            };
+            let function = match name {
+                Some(name) => name.to_lowercase(),
+                None => format!("ord{}", ordinal),
+            };
+            format!("{}.{}", library, function)
+        })
+        .collect();
+    to_hex(&Md5::digest(entries.join(",").as_bytes()))
+}
+
+/// Lists named exports from goblin along with the ordinal-only entries of
+/// the export address table, which goblin skips.
+fn exports(pe: &PE, data: &[u8]) -> Vec<ExportInfo> {
+    let Some(export_data) = &pe.export_data else {
+        return Vec::new();
+    };
+    let base = export_data.export_directory_table.ordinal_base;
+    let ordinals = &export_data.export_ordinal_table;
+
+    // goblin only drops named exports it fails to parse; when none were
+    // dropped they line up with the ordinal table.
+    let aligned = pe.exports.len() == ordinals.len();
+    let mut exports: Vec<ExportInfo> = pe
+        .exports
+        .iter()
+        .enumerate()
+        .map(|(index, export)| ExportInfo {
+            name: export.name.map(String::from),
+            ordinal: aligned.then(|| base + u32::from(ordinals[index])),
+            rva: export.rva as u64,
+            forwarder: export.reexport.as_ref().map(|reexport| match reexport {
+                Reexport::DLLName { export, lib } => format!("{}.{}", lib, export),
+                Reexport::DLLOrdinal { ordinal, lib } => format!("{}.#{}", lib, ordinal),
+            }),
+        })
+        .collect();
+
+    let file_alignment = pe
+        .header
+        .optional_header
+        .map_or(0, |header| header.windows_fields.file_alignment);
+    for (index, entry) in export_data.export_address_table.iter().enumerate() {
+        if ordinals.contains(&(index as u16)) {
+            continue;
+        }
+        let (rva, forwarder) = match *entry {
+            ExportAddressTableEntry::ExportRVA(0) => continue,
+            ExportAddressTableEntry::ExportRVA(rva) => (rva, None),
+            ExportAddressTableEntry::ForwarderRVA(rva) => {
+                let forwarder = goblin::pe::utils::find_offset(
+                    rva as usize,
+                    &pe.sections,
+                    file_alignment,
+                    &Default::default(),
+                )
+                .and_then(|offset| data.get(offset..))
+                .map(|bytes| {
+                    let end = bytes
+                        .iter()
+                        .position(|&byte| byte == 0)
+                        .unwrap_or(bytes.len());
+                    String::from_utf8_lossy(&bytes[..end]).into_owned()
+                });
+                (rva, forwarder)
+            }
+        };
+        exports.push(ExportInfo {
+            name: None,
+            ordinal: Some(base + index as u32),
+            rva: u64::from(rva),
+            forwarder,
+        });
+    }
+    exports.sort_by_key(|export| export.ordinal);
+    exports
+}
+
+/// Size of the COFF symbol table and the string table that follows it,
+/// which MinGW leaves in images.
+fn coff_symbol_tables_size(pe: &PE, data: &[u8]) -> u64 {
+    let coff = &pe.header.coff_header;
+    if coff.pointer_to_symbol_table == 0 {
+        return 0;
+    }
+    let symbols = u64::from(coff.number_of_symbol_table) * 18;
+    let strings_at = u64::from(coff.pointer_to_symbol_table) + symbols;
+    let strings = usize::try_from(strings_at)
+        .ok()
+        .and_then(|start| data.get(start..start.checked_add(4)?))
+        .map_or(0, |size| {
+            u64::from(u32::from_le_bytes([size[0], size[1], size[2], size[3]]))
+        });
+    symbols + strings
+}
+
+fn sizes(pe: &PE, data: &[u8], sections: &[SectionInfo]) -> SizeBreakdown {
+    let file_size = data.len() as u64;
+    let section_total: u64 = sections
+        .iter()
+        .filter(|section| section.file_backed)
+        .map(|section| section.size)
+        .sum();
+    let symbol_tables = coff_symbol_tables_size(pe, data);
+    // Like pefile, anything past the end of the last section's raw data is
+    // overlay, including a trailing COFF symbol table or signature.
+    let sections_end = sections
+        .iter()
+        .filter(|section| section.file_backed)
+        .map(|section| section.offset + section.size)
+        .max()
+        .unwrap_or(0);
+    let overlay = (sections_end > 0 && sections_end < file_size).then(|| Overlay {
+        offset: sections_end,
+        size: file_size - sections_end,
+    });
+    SizeBreakdown {
+        file_size,
+        sections: section_total,
+        symbol_tables,
+        unaccounted: file_size.saturating_sub(section_total + symbol_tables),
+        overlay,
+    }
+}
+
+fn rich_header(pe: &PE) -> Option<RichHeader> {
+    let rich = pe.header.rich_header?;
+    Some(RichHeader {
+        key: rich.key,
+        entries: rich
+            .metadatas()
+            .map_while(Result::ok)
+            .map(|metadata| RichEntry {
+                comp_id: u32::from(metadata.product) << 16 | u32::from(metadata.build),
+                product: metadata.product,
+                build: metadata.build,
+                count: metadata.use_count,
+            })
+            .collect(),
+    })
+}
+
+pub(crate) fn summarize(pe: &PE, data: &[u8], options: &AnalyzeOptions) -> BinaryReport {
+    let mut report = BinaryReport::new("PE");
+    report.arch = Some(String::from(goblin::pe::header::machine_to_str(
+        pe.header.coff_header.machine,
+    )));
+    report.class = Some(String::from(if pe.is_64 { "PE32+" } else { "PE32" }));
+    report.endianness = Some(String::from("little"));
+    report.entry = Some(pe.entry as u64);
+    report.timestamp = Some(pe.header.coff_header.time_date_stamp);
+    report.rich_header = rich_header(pe);
+    if let Some(header) = &pe.header.optional_header {
+        report.linker_version = Some(format!(
+            "{}.{:02}",
+            header.standard_fields.major_linker_version,
+            header.standard_fields.minor_linker_version
+        ));
+        report.load_base = Some(header.windows_fields.image_base);
+        let characteristics = header.windows_fields.dll_characteristics;
+        report.aslr =
+            Some(characteristics & dll_characteristic::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0);
+        report.nx_compat =
+            Some(characteristics & dll_characteristic::IMAGE_DLLCHARACTERISTICS_NX_COMPAT != 0);
+    }
+    // Neither a debug directory (e.g. a CodeView/PDB reference) nor a COFF
+    // symbol table, as left behind by MinGW builds.
+    report.stripped =
+        Some(pe.debug_data.is_none() && pe.header.coff_header.pointer_to_symbol_table == 0);
+    report.libraries = dependencies(pe).into_iter().map(String::from).collect();
+    report.sections = sections(pe);
+    report.debug_info = Some(dwarf::from_sections(&report.sections));
+    report.sizes = Some(sizes(pe, data, &report.sections));
+    report.imports = pe
+        .imports
+        .iter()
+        .map(|import| {
+            let ordinal = import_is_ordinal(import).then_some(import.ordinal);
+            ImportInfo {
+                library: Some(String::from(import.dll)),
+                name: match ordinal {
+                    Some(ordinal) => format!("Ordinal#{}", ordinal),
+                    None => import.name.to_string(),
+                },
+                ordinal,
+            }
+        })
+        .collect();
+    report.exports = exports(pe, data);
+    if options.imphash {
+        report.imphash = Some(imphash(pe));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imphash_matches_pefile() {
+        let imports = [
+            ("KERNEL32.dll", Some("GetProcAddress"), 0),
+            ("KERNEL32.dll", Some("LoadLibraryA"), 0),
+            ("COMCTL32.dll", None, 17),
+            ("USER32.DLL", Some("MessageBoxW"), 0),
+            ("MSCOMCTL.OCX", Some("DllGetClassObject"), 0),
+            ("hal.sys", Some("KeGetCurrentIrql"), 0),
+            ("ntoskrnl.exe", Some("IoCompleteRequest"), 0),
+        ];
+        // MD5 of "kernel32.getprocaddress,kernel32.loadlibrarya,comctl32.ord17,
+        // user32.messageboxw,mscomctl.dllgetclassobject,hal.kegetcurrentirql,
+        // ntoskrnl.exe.iocompleterequest".
+        assert_eq!(hash_imports(&imports), "1da7403d7e06975072841c459d5ade08");
+    }
+
+    #[test]
+    fn imphash_without_imports_is_empty() {
+        assert_eq!(hash_imports(&[]), "");
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Deserialize;
+
+use crate::report::BinaryReport;
+
+/// Hardening requirements checked by `--policy`, written in TOML or JSON.
+/// Every key is optional and defaults to no requirement:
+///
+/// ```toml
+/// require_pie = true
+/// require_nx = true
+/// forbid_wx_segments = true
+/// forbid_imports = ["system", "strcpy"]
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// ELF binaries must be position-independent, and PEs must set
+    /// `DYNAMIC_BASE`. Other formats aren't checked.
+    pub require_pie: bool,
+    /// ELF stacks must not be executable, and PEs must set `NX_COMPAT`.
+    /// Other formats aren't checked.
+    pub require_nx: bool,
+    /// No ELF segment or PE section may be both writable and executable.
+    pub forbid_wx_segments: bool,
+    /// Functions that must not be imported, matched exactly.
+    pub forbid_imports: Vec<String>,
+}
+
+impl Policy {
+    pub fn from_toml(text: &str) -> Result<Policy, String> {
+        toml::from_str(text).map_err(|err| err.to_string().trim_end().to_string())
+    }
+
+    pub fn from_json(text: &str) -> Result<Policy, String> {
+        serde_json::from_str(text).map_err(|err| err.to_string())
+    }
+
+    /// Returns one message per requirement `report` fails.
+    pub fn violations(&self, report: &BinaryReport) -> Vec<String> {
+        let mut violations = Vec::new();
+        // PE has its own flags for the same protections; formats with
+        // neither, such as archives, aren't checked.
+        let hardening = [
+            (self.require_pie, "PIE", "DYNAMIC_BASE", report.pie()),
+            (self.require_nx, "NX", "NX_COMPAT", report.nx()),
+        ];
+        for (required, elf_name, pe_name, enabled) in hardening {
+            let name = if report.format == "PE" {
+                pe_name
+            } else {
+                elf_name
+            };
+            if required && enabled == Some(false) {
+                violations.push(format!("{} is disabled", name));
+            }
+        }
+        if self.forbid_wx_segments {
+            for segment in &report.segments {
+                if segment.flags.contains('w') && segment.flags.contains('x') {
+                    violations.push(format!(
+                        "{} segment at {:#x} is writable and executable",
+                        segment.kind, segment.address
+                    ));
+                }
+            }
+            if report.format == "PE" {
+                for section in &report.sections {
+                    if section.flags.contains('W') && section.flags.contains('X') {
+                        violations.push(format!(
+                            "section {} is writable and executable",
+                            section.name
+                        ));
+                    }
+                }
+            }
+        }
+        for import in &report.imports {
+            if self.forbid_imports.contains(&import.name) {
+                violations.push(format!("imports forbidden function '{}'", import.name));
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Checksec, ImportInfo, SegmentInfo};
+
+    const STRICT: &str = r#"
+        require_pie = true
+        require_nx = true
+        forbid_wx_segments = true
+        forbid_imports = ["strcpy"]
+    "#;
+
+    fn elf(pie: bool, nx: bool) -> BinaryReport {
+        let mut report = BinaryReport::new("ELF");
+        report.checksec = Some(Checksec {
+            pie,
+            nx,
+            relro: String::from("full"),
+            canary: true,
+        });
+        report
+    }
+
+    #[test]
+    fn hardened_elf_passes() {
+        let policy = Policy::from_toml(STRICT).unwrap();
+        assert!(policy.violations(&elf(true, true)).is_empty());
+    }
+
+    #[test]
+    fn reports_every_failed_requirement() {
+        let policy = Policy::from_toml(STRICT).unwrap();
+        let mut report = elf(false, false);
+        report.segments.push(SegmentInfo {
+            kind: String::from("PT_LOAD"),
+            flags: String::from("rwx"),
+            offset: 0,
+            address: 0x1000,
+            file_size: 0x10,
+            memory_size: 0x10,
+        });
+        report.imports.push(ImportInfo {
+            library: None,
+            name: String::from("strcpy"),
+            ordinal: None,
+        });
+        assert_eq!(
+            policy.violations(&report),
+            [
+                "PIE is disabled",
+                "NX is disabled",
+                "PT_LOAD segment at 0x1000 is writable and executable",
+                "imports forbidden function 'strcpy'",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_pe_flags_and_skips_other_formats() {
+        let policy = Policy::from_json(r#"{"require_pie": true, "require_nx": true}"#).unwrap();
+        let mut pe = BinaryReport::new("PE");
+        pe.aslr = Some(false);
+        pe.nx_compat = Some(true);
+        assert_eq!(policy.violations(&pe), ["DYNAMIC_BASE is disabled"]);
+        assert!(policy.violations(&BinaryReport::new("archive")).is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(Policy::from_toml("require_canary = true").is_err());
+    }
+}
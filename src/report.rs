@@ -0,0 +1,387 @@
+//! The structured result of analyzing a binary.
+
+use serde::{Deserialize, Serialize};
+
+/// Everything we know about a parsed binary, independent of how it gets
+/// printed. The top-level keys are the stable part of the `--json` output.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BinaryReport {
+    pub format: String,
+    pub arch: Option<String>,
+    pub class: Option<String>,
+    pub endianness: Option<String>,
+    pub entry: Option<u64>,
+    /// The preferred load address: the lowest `PT_LOAD` address of an ELF,
+    /// or a PE's `ImageBase`.
+    pub load_base: Option<u64>,
+    /// Whether the loader can place the image at a randomized address: an
+    /// ELF is position-independent, or a PE opts in with `DYNAMIC_BASE`.
+    pub aslr: Option<bool>,
+    /// Whether a PE opts in to DEP with `NX_COMPAT`; ELF has
+    /// [`Checksec::nx`] instead.
+    pub nx_compat: Option<bool>,
+    pub magic: Option<u64>,
+    pub interpreter: Option<String>,
+    /// Whether the symbol table (ELF) or debug information (PE) has been
+    /// removed; `None` for formats where we don't check.
+    pub stripped: Option<bool>,
+    pub debug_info: Option<DebugInfo>,
+    pub sizes: Option<SizeBreakdown>,
+    /// The `NT_GNU_BUILD_ID` note as lowercase hex, as used by debuginfod.
+    pub build_id: Option<String>,
+    /// The OS and minimum kernel version from `NT_GNU_ABI_TAG`, e.g.
+    /// "Linux 3.2.0".
+    pub abi_tag: Option<String>,
+    /// The Mach-O `LC_UUID`, which dSYM bundles are matched on.
+    pub uuid: Option<String>,
+    /// The PE `TimeDateStamp`, in seconds since the Unix epoch. Reproducible
+    /// builds put a hash here instead.
+    pub timestamp: Option<u32>,
+    /// The PE linker version, e.g. "14.29".
+    pub linker_version: Option<String>,
+    /// The decoded PE Rich header; `None` in PEs that don't have one, which
+    /// is usual for non-MSVC toolchains.
+    pub rich_header: Option<RichHeader>,
+    pub libraries: Vec<String>,
+    /// The libraries resolved recursively, with `--deps-tree`.
+    pub dependency_tree: Option<Vec<Dependency>>,
+    pub sections: Vec<SectionInfo>,
+    pub segments: Vec<SegmentInfo>,
+    /// The ELF `PT_TLS` segment; `None` if the binary has no thread-local
+    /// storage.
+    pub tls: Option<TlsInfo>,
+    pub load_commands: Vec<LoadCommandInfo>,
+    pub symbols: Vec<SymbolInfo>,
+    pub relocations: Vec<RelocationInfo>,
+    pub imports: Vec<ImportInfo>,
+    pub exports: Vec<ExportInfo>,
+    pub checksec: Option<Checksec>,
+    pub members: Vec<Member>,
+    pub hashes: Option<Hashes>,
+    pub imphash: Option<String>,
+    pub strings: Option<Vec<FoundString>>,
+    pub dump: Option<SectionDump>,
+    /// The requirements of [`crate::AnalyzeOptions::policy`] this binary
+    /// fails, checked before any filter is applied.
+    pub violations: Vec<String>,
+}
+
+impl BinaryReport {
+    pub(crate) fn new(format: &str) -> Self {
+        BinaryReport {
+            format: String::from(format),
+            arch: None,
+            class: None,
+            endianness: None,
+            entry: None,
+            load_base: None,
+            aslr: None,
+            nx_compat: None,
+            magic: None,
+            interpreter: None,
+            stripped: None,
+            debug_info: None,
+            sizes: None,
+            build_id: None,
+            abi_tag: None,
+            uuid: None,
+            timestamp: None,
+            linker_version: None,
+            rich_header: None,
+            libraries: Vec::new(),
+            dependency_tree: None,
+            sections: Vec::new(),
+            segments: Vec::new(),
+            tls: None,
+            load_commands: Vec::new(),
+            symbols: Vec::new(),
+            relocations: Vec::new(),
+            imports: Vec::new(),
+            exports: Vec::new(),
+            checksec: None,
+            members: Vec::new(),
+            hashes: None,
+            imphash: None,
+            strings: None,
+            dump: None,
+            violations: Vec::new(),
+        }
+    }
+
+    /// Whether the binary is position-independent: [`Checksec::pie`] for an
+    /// ELF, `DYNAMIC_BASE` for a PE, and `None` for other formats.
+    pub fn pie(&self) -> Option<bool> {
+        match self.format.as_str() {
+            "ELF" => self.checksec.as_ref().map(|checksec| checksec.pie),
+            "PE" => self.aslr,
+            _ => None,
+        }
+    }
+
+    /// Whether the stack and data can't be executed: [`Checksec::nx`] for an
+    /// ELF, `NX_COMPAT` for a PE, and `None` for other formats.
+    pub fn nx(&self) -> Option<bool> {
+        match self.format.as_str() {
+            "ELF" => self.checksec.as_ref().map(|checksec| checksec.nx),
+            "PE" => self.nx_compat,
+            _ => None,
+        }
+    }
+
+    /// The function whose `[address, address + size)` range contains
+    /// `address`.
+    pub fn function_at(&self, address: u64) -> Option<&SymbolInfo> {
+        self.symbols.iter().find(|symbol| {
+            symbol.kind == "FUNC"
+                && symbol.section != "UND"
+                && (symbol.address..symbol.address.saturating_add(symbol.size)).contains(&address)
+        })
+    }
+
+    /// The loaded section containing `address`. Sections that aren't mapped
+    /// have an address of 0 and never match.
+    pub fn section_at(&self, address: u64) -> Option<&SectionInfo> {
+        self.sections.iter().find(|section| {
+            section.address != 0
+                && (section.address..section.address.saturating_add(section.size))
+                    .contains(&address)
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SectionInfo {
+    pub name: String,
+    pub kind: Option<String>,
+    pub address: u64,
+    pub offset: u64,
+    pub size: u64,
+    /// False for sections that occupy memory but no bytes in the file,
+    /// such as `.bss`.
+    pub file_backed: bool,
+    /// Permission and attribute flags: `readelf` letters such as "AX" for
+    /// ELF, or "RWX" plus content letters for PE.
+    pub flags: String,
+    pub alignment: u64,
+    /// Shannon entropy in bits per byte, filled in when
+    /// [`crate::AnalyzeOptions::entropy`] is set.
+    pub entropy: Option<f64>,
+}
+
+impl SectionInfo {
+    /// The section's contents in `data`, or `None` if it has no file backing
+    /// or its range falls outside the file.
+    pub fn bytes<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        if !self.file_backed || self.size == 0 {
+            return None;
+        }
+        let start = usize::try_from(self.offset).ok()?;
+        let end = start.checked_add(usize::try_from(self.size).ok()?)?;
+        data.get(start..end)
+    }
+}
+
+/// An ELF program header, i.e. what the loader maps into memory.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SegmentInfo {
+    pub kind: String,
+    /// Permissions in `rwx` form, e.g. "r-x".
+    pub flags: String,
+    pub offset: u64,
+    pub address: u64,
+    pub file_size: u64,
+    pub memory_size: u64,
+}
+
+/// A Mach-O load command.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LoadCommandInfo {
+    /// The command's name, e.g. "LC_LOAD_DYLIB", or its hex value when we
+    /// don't know it.
+    pub kind: String,
+    pub offset: u64,
+    pub size: u64,
+    /// The interesting payload, such as a dylib path and version or a
+    /// segment name.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
+    pub kind: String,
+    /// e.g. "GLOBAL" or "WEAK".
+    pub binding: String,
+    /// e.g. "DEFAULT" or "HIDDEN".
+    pub visibility: String,
+    /// Name of the section the symbol is defined in, or "UND", "ABS" or
+    /// "COM".
+    pub section: String,
+    /// Whether the symbol is in `.dynsym`. A symbol in both tables at the
+    /// same address is listed once.
+    pub dynamic: bool,
+    pub demangled: Option<String>,
+}
+
+/// The thread-local storage template each thread's block is created from.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TlsInfo {
+    pub address: u64,
+    /// Size of each thread's block.
+    pub size: u64,
+    pub alignment: u64,
+    /// Bytes copied from the file (`.tdata`).
+    pub initialized: u64,
+    /// Bytes zeroed at thread creation (`.tbss`).
+    pub zero_filled: u64,
+    /// Total size of the `SHF_TLS` sections holding data, and of those
+    /// taking up no space in the file.
+    pub tdata_sections: u64,
+    pub tbss_sections: u64,
+}
+
+/// A dynamic or PLT relocation applied by the ELF loader.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RelocationInfo {
+    /// The section the entry comes from, e.g. ".rela.plt".
+    pub table: String,
+    pub offset: u64,
+    /// The architecture's name for the type, e.g. "R_X86_64_JUMP_SLOT", or
+    /// the numeric code when we have no name for it.
+    pub kind: String,
+    pub symbol: Option<String>,
+    pub addend: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ImportInfo {
+    pub library: Option<String>,
+    /// The imported symbol, or `Ordinal#<n>` when imported by ordinal only.
+    pub name: String,
+    pub ordinal: Option<u16>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExportInfo {
+    /// `None` for exports that are only reachable by ordinal.
+    pub name: Option<String>,
+    pub ordinal: Option<u32>,
+    pub rva: u64,
+    /// Target of a forwarded export, e.g. "NTDLL.RtlAllocateHeap".
+    pub forwarder: Option<String>,
+}
+
+/// A binary nested inside a container, such as one slice of a Mach-O
+/// universal binary or one member of a static archive.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Member {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+    // Kept as "summary" in JSON, which predates the library split.
+    #[serde(rename = "summary")]
+    pub report: Option<BinaryReport>,
+    pub error: Option<String>,
+}
+
+/// A run of printable characters found by [`crate::AnalyzeOptions::strings`].
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FoundString {
+    /// File offset of the first byte of the string.
+    pub offset: u64,
+    /// Either "ascii" or "utf-16le".
+    pub encoding: String,
+    pub value: String,
+}
+
+/// The raw contents of the section requested with [`crate::AnalyzeOptions::dump`].
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SectionDump {
+    pub section: String,
+    pub offset: u64,
+    /// `None` when the section has no file backing (e.g. `SHT_NOBITS`).
+    pub bytes: Option<Vec<u8>>,
+}
+
+/// Lowercase hex digests of the whole file, limited to the algorithms
+/// requested in [`crate::AnalyzeOptions::hashes`].
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Hashes {
+    pub md5: Option<String>,
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+}
+
+/// Whether the binary carries DWARF, and how many bytes of it.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DebugInfo {
+    pub dwarf: bool,
+    /// Total file size of the `.debug_*` sections (or the Mach-O `__DWARF`
+    /// segment), including compressed `.zdebug_*` ones.
+    pub size: u64,
+}
+
+/// A needed library and, the first time it is seen, the libraries it
+/// needs in turn.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Dependency {
+    pub name: String,
+    /// Where the library was found; `None` if it is missing.
+    pub path: Option<String>,
+    /// Its dependencies were listed earlier in the tree.
+    pub already_shown: bool,
+    pub needed: Vec<Dependency>,
+}
+
+/// The undocumented header MSVC's linker leaves between the DOS stub and
+/// the PE header, listing the tools that produced the object files.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RichHeader {
+    /// The XOR key, which doubles as a checksum of the DOS header and
+    /// entries.
+    pub key: u32,
+    pub entries: Vec<RichEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RichEntry {
+    /// `product << 16 | build`, the "comp.id" tools match on.
+    pub comp_id: u32,
+    pub product: u16,
+    pub build: u16,
+    /// How many objects this tool contributed.
+    pub count: u32,
+}
+
+/// How the bytes of the file are accounted for.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SizeBreakdown {
+    pub file_size: u64,
+    /// Sum of the file-backed section sizes.
+    pub sections: u64,
+    /// Symbol and string tables. For ELF these are sections and so already
+    /// counted in `sections`; a PE's COFF symbol table lies outside them.
+    pub symbol_tables: u64,
+    /// Bytes covered by neither: headers, padding and any overlay.
+    pub unaccounted: u64,
+    /// PE only: data appended after the last section.
+    pub overlay: Option<Overlay>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Overlay {
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Hardening features of an ELF executable, as reported by `checksec`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Checksec {
+    pub pie: bool,
+    pub nx: bool,
+    /// One of "full", "partial" or "disabled".
+    pub relro: String,
+    pub canary: bool,
+}
@@ -0,0 +1,101 @@
+use crate::report::FoundString;
+
+fn is_printable(byte: u8) -> bool {
+    byte == b'\t' || (0x20..0x7f).contains(&byte)
+}
+
+/// Finds runs of at least `min_len` printable ASCII characters in `bytes`,
+/// and also UTF-16LE runs when `utf16` is set. `base` is the file offset of
+/// `bytes`, so reported offsets always refer to the whole file.
+pub fn find_strings(bytes: &[u8], base: u64, min_len: usize, utf16: bool) -> Vec<FoundString> {
+    let mut found = Vec::new();
+
+    let mut start = 0;
+    for (index, &byte) in bytes.iter().chain([0].iter()).enumerate() {
+        if is_printable(byte) {
+            continue;
+        }
+        if index - start >= min_len {
+            found.push(FoundString {
+                offset: base + start as u64,
+                encoding: String::from("ascii"),
+                value: String::from_utf8_lossy(&bytes[start..index]).into_owned(),
+            });
+        }
+        start = index + 1;
+    }
+
+    if utf16 {
+        for parity in 0..2 {
+            let mut run = String::new();
+            let mut run_start = parity;
+            let mut index = parity;
+            while index + 1 < bytes.len() {
+                let (low, high) = (bytes[index], bytes[index + 1]);
+                if high == 0 && is_printable(low) {
+                    if run.is_empty() {
+                        run_start = index;
+                    }
+                    run.push(char::from(low));
+                } else {
+                    if run.len() >= min_len {
+                        found.push(FoundString {
+                            offset: base + run_start as u64,
+                            encoding: String::from("utf-16le"),
+                            value: run.clone(),
+                        });
+                    }
+                    run.clear();
+                }
+                index += 2;
+            }
+            if run.len() >= min_len {
+                found.push(FoundString {
+                    offset: base + run_start as u64,
+                    encoding: String::from("utf-16le"),
+                    value: run,
+                });
+            }
+        }
+        found.sort_by_key(|string| string.offset);
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(found: &[FoundString]) -> Vec<(u64, &str, &str)> {
+        found
+            .iter()
+            .map(|string| {
+                (
+                    string.offset,
+                    string.encoding.as_str(),
+                    string.value.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_ascii_runs_of_min_len() {
+        let bytes = b"\x00abc\x01abcd\x7fhello\tworld";
+        assert_eq!(
+            summary(&find_strings(bytes, 0x100, 4, false)),
+            [(0x105, "ascii", "abcd"), (0x10a, "ascii", "hello\tworld")]
+        );
+    }
+
+    #[test]
+    fn finds_utf16le_runs_at_either_parity() {
+        let bytes = b"\x01W\x00i\x00n\x00\x00\x00ab\x00";
+        assert_eq!(
+            summary(&find_strings(bytes, 0, 3, true)),
+            [(1, "utf-16le", "Win")]
+        );
+        assert!(find_strings(bytes, 0, 3, false).is_empty());
+    }
+}